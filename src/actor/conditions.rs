@@ -0,0 +1,291 @@
+use serde::{Deserialize, Serialize};
+
+///Something hindering the character or a creature for a while
+#[derive(Clone, Copy, Debug, Serialize, Deserialize, PartialEq)]
+pub enum Condition {
+    Blinded,
+    Charmed,
+    Frightened,
+    Poisoned,
+    Prone,
+    Restrained,
+    Stunned,
+}
+
+impl Condition {
+    pub fn name(&self) -> &str {
+        match self {
+            Condition::Blinded => "blinded",
+            Condition::Charmed => "charmed",
+            Condition::Frightened => "frightened",
+            Condition::Poisoned => "poisoned",
+            Condition::Prone => "prone",
+            Condition::Restrained => "restrained",
+            Condition::Stunned => "stunned",
+        }
+    }
+
+    pub fn description(&self) -> &str {
+        match self {
+            Condition::Blinded => "Sees only what is next to it. Its attacks have disadvantage and attacks against it advantage.",
+            Condition::Charmed => "Will not attack anyone.",
+            Condition::Frightened => "Its attacks have disadvantage, and a frightened creature runs.",
+            Condition::Poisoned => "Its attacks have disadvantage.",
+            Condition::Prone => "Its attacks have disadvantage. Attacks against it have advantage up close and disadvantage from further off. Moving stands it up.",
+            Condition::Restrained => "Can not move. Its attacks and dexterity saving throws have disadvantage, and attacks against it advantage.",
+            Condition::Stunned => "Can not act, and fails strength and dexterity saving throws. Attacks against it have advantage.",
+        }
+    }
+
+    ///What race effects giving advantage on saving throws against the
+    ///condition call it
+    pub fn advantage_key(&self) -> &str {
+        match self {
+            Condition::Poisoned => "poison",
+            _ => self.name(),
+        }
+    }
+}
+
+///A condition an attack or spell leaves its target with
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Inflicts {
+    pub condition: Condition,
+    //Seconds it lasts
+    pub duration: u32,
+    //Ability and difficulty class of the saving throw to avoid it, spells
+    //use their own instead
+    #[serde(default)]
+    pub save: Option<String>,
+    #[serde(default)]
+    pub dc: u32,
+}
+
+///Conditions in effect, and the game time each ends
+///
+///A condition does not stack with itself, having it again only makes it
+///last longer if the new one ends later. Different conditions all apply,
+///though several giving disadvantage still give it only once, and advantage
+///and disadvantage together cancel out
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Conditions {
+    conditions: Vec<(Condition, u32)>,
+}
+
+impl Conditions {
+    ///Add a condition lasting until a game time
+    ///
+    ///Returns true if it was not already in effect
+    pub fn add(
+        &mut self,
+        condition: Condition,
+        until: u32,
+    ) -> bool {
+        match self
+            .conditions
+            .iter_mut()
+            .find(|(existing, _)| *existing == condition)
+        {
+            Some((_, end)) => {
+                *end = (*end).max(until);
+                false
+            }
+            None => {
+                self.conditions.push((condition, until));
+                true
+            }
+        }
+    }
+
+    ///Returns true if the condition was in effect
+    pub fn remove(
+        &mut self,
+        condition: Condition,
+    ) -> bool {
+        let count = self.conditions.len();
+        self.conditions
+            .retain(|(existing, _)| *existing != condition);
+        self.conditions.len() != count
+    }
+
+    pub fn has(
+        &self,
+        condition: Condition,
+    ) -> bool {
+        self.conditions
+            .iter()
+            .any(|(existing, _)| *existing == condition)
+    }
+
+    ///Remove the conditions that have ended by a game time, returning them
+    pub fn expire(
+        &mut self,
+        time: u32,
+    ) -> Vec<Condition> {
+        let (ended, left): (Vec<_>, Vec<_>) =
+            self.conditions.drain(..).partition(|(_, end)| *end <= time);
+        self.conditions = left;
+        ended.into_iter().map(|(condition, _)| condition).collect()
+    }
+
+    ///Conditions in effect, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = Condition> + '_ {
+        self.conditions.iter().map(|(condition, _)| *condition)
+    }
+
+    ///Attacks made under these conditions have disadvantage
+    pub fn attack_disadvantage(&self) -> bool {
+        self.conditions.iter().any(|(condition, _)| {
+            matches!(
+                condition,
+                Condition::Blinded
+                    | Condition::Frightened
+                    | Condition::Poisoned
+                    | Condition::Prone
+                    | Condition::Restrained
+            )
+        })
+    }
+
+    ///Advantage and disadvantage on attacks against someone under these
+    ///conditions, from a distance in tiles
+    pub fn attacked(
+        &self,
+        distance: usize,
+    ) -> (bool, bool) {
+        let advantage = self
+            .conditions
+            .iter()
+            .any(|(condition, _)| match condition {
+                Condition::Blinded | Condition::Restrained | Condition::Stunned => true,
+                Condition::Prone => distance <= 1,
+                _ => false,
+            });
+        let disadvantage = self.has(Condition::Prone) && distance > 1;
+        (advantage, disadvantage)
+    }
+
+    pub fn can_act(&self) -> bool {
+        !self.has(Condition::Stunned)
+    }
+
+    pub fn can_move(&self) -> bool {
+        !self.has(Condition::Restrained) && self.can_act()
+    }
+
+    pub fn can_attack(&self) -> bool {
+        !self.has(Condition::Charmed) && self.can_act()
+    }
+
+    ///Feet that can be moved at a speed, none while held in place
+    ///
+    ///Turns still come round at the full speed, conditions only stop moving
+    pub fn movement(
+        &self,
+        speed: u32,
+    ) -> u32 {
+        if self.can_move() {
+            speed
+        } else {
+            0
+        }
+    }
+
+    ///Tiles that can be seen at most, None if sight is not hindered
+    pub fn sight_limit(&self) -> Option<usize> {
+        if self.has(Condition::Blinded) {
+            Some(1)
+        } else {
+            None
+        }
+    }
+
+    ///Saving throws with an ability have disadvantage
+    pub fn save_disadvantage(
+        &self,
+        ability: &str,
+    ) -> bool {
+        ability == "dexterity" && self.has(Condition::Restrained)
+    }
+
+    ///Saving throws with an ability fail without a roll
+    pub fn fails_save(
+        &self,
+        ability: &str,
+    ) -> bool {
+        (ability == "strength" || ability == "dexterity") && self.has(Condition::Stunned)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_condition_does_not_stack() {
+        let mut conditions = Conditions::default();
+        assert!(conditions.add(Condition::Poisoned, 60));
+        assert!(!conditions.add(Condition::Poisoned, 30));
+        assert_eq!(conditions.iter().count(), 1);
+        //The later end is kept
+        assert!(conditions.expire(59).is_empty());
+        assert!(!conditions.add(Condition::Poisoned, 90));
+        assert_eq!(conditions.expire(60), Vec::new());
+        assert_eq!(conditions.expire(90), vec![Condition::Poisoned]);
+    }
+
+    #[test]
+    fn conditions_end_on_time() {
+        let mut conditions = Conditions::default();
+        conditions.add(Condition::Prone, 10);
+        conditions.add(Condition::Blinded, 20);
+        assert_eq!(conditions.expire(10), vec![Condition::Prone]);
+        assert!(conditions.has(Condition::Blinded));
+        assert!(conditions.remove(Condition::Blinded));
+        assert!(!conditions.remove(Condition::Blinded));
+        assert_eq!(conditions.iter().count(), 0);
+    }
+
+    #[test]
+    fn prone_helps_close_attackers_and_hinders_distant_ones() {
+        let mut conditions = Conditions::default();
+        conditions.add(Condition::Prone, 10);
+        assert_eq!(conditions.attacked(1), (true, false));
+        assert_eq!(conditions.attacked(5), (false, true));
+        //Restrained as well, far off attackers have both and they cancel out
+        conditions.add(Condition::Restrained, 10);
+        assert_eq!(conditions.attacked(5), (true, true));
+    }
+
+    #[test]
+    fn stunned_stops_everything() {
+        let mut conditions = Conditions::default();
+        conditions.add(Condition::Stunned, 10);
+        assert!(!conditions.can_act());
+        assert!(!conditions.can_move());
+        assert!(!conditions.can_attack());
+        assert!(conditions.fails_save("dexterity"));
+        assert!(!conditions.fails_save("wisdom"));
+    }
+
+    #[test]
+    fn restrained_and_charmed_stop_only_their_own() {
+        let mut conditions = Conditions::default();
+        conditions.add(Condition::Restrained, 10);
+        assert!(!conditions.can_move());
+        assert!(conditions.can_attack());
+        assert!(conditions.save_disadvantage("dexterity"));
+        conditions.add(Condition::Charmed, 10);
+        assert!(!conditions.can_attack());
+        assert!(conditions.can_act());
+    }
+
+    #[test]
+    fn blinded_limits_sight() {
+        let mut conditions = Conditions::default();
+        assert_eq!(conditions.sight_limit(), None);
+        conditions.add(Condition::Blinded, 10);
+        assert_eq!(conditions.sight_limit(), Some(1));
+        assert!(conditions.attack_disadvantage());
+    }
+}
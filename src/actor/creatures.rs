@@ -0,0 +1,239 @@
+use crate::actor::conditions::{Condition, Conditions, Inflicts};
+use crate::actor::{ability_key, ability_modifier, Stats};
+use crate::utils::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct CreatureAttack {
+    pub attack: String,
+    pub to_hit: i32,
+    damage: String,
+    //Condition left on a target that fails the saving throw
+    #[serde(default)]
+    pub inflicts: Option<Inflicts>,
+}
+
+impl CreatureAttack {
+    pub fn damage_dice(&self) -> Dice {
+        Dice::parse(self.damage.split(' ').next().unwrap_or("")).unwrap_or_default()
+    }
+
+    pub fn damage_type(&self) -> String {
+        self.damage.split(' ').nth(1).unwrap_or("").to_string()
+    }
+}
+
+///Shallowest and deepest levels a creature is found on
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+struct CreatureDepth {
+    min: usize,
+    max: usize,
+}
+
+///How a creature goes about the level, see the ai module
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub enum Behaviour {
+    ///Waits where it is until it notices the player, then gives chase
+    #[default]
+    Hunt,
+    ///Roams the level, giving chase once it notices the player
+    Wander,
+    ///Keeps to where it was placed, only chasing the player a short way
+    Guard,
+    ///Roams, alerts others of its kind and surrounds the player
+    Pack,
+}
+
+///A creature from the catalogue, and its state once placed on a level
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Creature {
+    creature: String,
+    symbol: char,
+    description: String,
+    hit_dice: String,
+    armor_class: i32,
+    speed: u32,
+    pub abilities: Stats,
+    attacks: Vec<CreatureAttack>,
+    experience: u32,
+    depth: CreatureDepth,
+    #[serde(default)]
+    behaviour: Behaviour,
+    //Percentage of its hit points below which it runs, 0 to fight to the end
+    #[serde(default)]
+    flee_below: u32,
+    #[serde(default)]
+    pub position: Point,
+    #[serde(default)]
+    hit_points: u32,
+    #[serde(default)]
+    max_hit_points: u32,
+    //Has noticed the player
+    #[serde(default)]
+    pub aware: bool,
+    //Tells creatures on the same level apart
+    #[serde(default)]
+    pub id: u32,
+    //Where it was placed, which a guard keeps to
+    #[serde(default)]
+    pub home: Point,
+    //Where it last saw the player, or heard of them
+    #[serde(default)]
+    pub last_seen: Option<Point>,
+    //Where a roaming creature is heading
+    #[serde(default)]
+    pub destination: Option<Point>,
+    #[serde(default)]
+    pub conditions: Conditions,
+}
+
+impl Creature {
+    pub fn name(&self) -> String {
+        self.creature.clone()
+    }
+
+    pub fn symbol(&self) -> char {
+        self.symbol
+    }
+
+    pub fn armor_class(&self) -> i32 {
+        self.armor_class
+    }
+
+    ///Speed turns come round at, conditions stop the creature moving but
+    ///not acting, as for the character
+    pub fn current_speed(&self) -> u32 {
+        self.speed
+    }
+
+    ///Feet the creature can move, none while held in place
+    pub fn movement_speed(&self) -> u32 {
+        self.conditions.movement(self.current_speed())
+    }
+
+    pub fn attacks(&self) -> &[CreatureAttack] {
+        &self.attacks
+    }
+
+    pub fn experience(&self) -> u32 {
+        self.experience
+    }
+
+    pub fn behaviour(&self) -> Behaviour {
+        self.behaviour
+    }
+
+    ///Check if it is hurt badly enough to run, or too scared to stay
+    pub fn is_fleeing(&self) -> bool {
+        self.hit_points * 100 < self.max_hit_points * self.flee_below
+            || self.conditions.has(Condition::Frightened)
+    }
+
+    pub fn hit_points(&self) -> u32 {
+        self.hit_points
+    }
+
+    pub fn max_hit_points(&self) -> u32 {
+        self.max_hit_points
+    }
+
+    ///Bonus to saving throws with an ability
+    pub fn saving_throw_modifier(
+        &self,
+        ability: &str,
+    ) -> i32 {
+        ability_modifier(self.abilities.ability(ability_key(ability)))
+    }
+
+    pub fn is_dead(&self) -> bool {
+        self.hit_points == 0
+    }
+
+    pub fn take_damage(
+        &mut self,
+        damage: u32,
+    ) {
+        self.hit_points = self.hit_points.saturating_sub(damage);
+    }
+
+    pub fn heal(
+        &mut self,
+        hit_points: u32,
+    ) {
+        self.hit_points = (self.hit_points + hit_points).min(self.max_hit_points);
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Creatures {
+    creatures: Vec<Creature>,
+}
+
+impl Default for Creatures {
+    fn default() -> Self {
+        Creatures::new()
+    }
+}
+
+impl Creatures {
+    pub fn new() -> Creatures {
+        let creature_json = include_str!("creatures.json");
+        let creatures: Vec<Creature> = serde_json::from_str(creature_json).unwrap();
+        Creatures { creatures }
+    }
+
+    pub fn value(
+        &self,
+        key: &str,
+    ) -> Option<&Creature> {
+        self.creatures
+            .iter()
+            .find(|creature| creature.creature == key)
+    }
+
+    ///Creatures found at a depth
+    pub fn keys_for_depth(
+        &self,
+        depth: usize,
+    ) -> Vec<String> {
+        self.creatures
+            .iter()
+            .filter(|creature| creature.depth.min <= depth && depth <= creature.depth.max)
+            .map(|creature| creature.creature.clone())
+            .collect()
+    }
+
+    ///A new creature at a position, with hit points rolled from its hit dice
+    pub fn spawn(
+        &self,
+        key: &str,
+        position: Point,
+    ) -> Option<Creature> {
+        let mut creature = self.value(key)?.clone();
+        let hit_points = Dice::parse(&creature.hit_dice)
+            .unwrap_or_default()
+            .roll()
+            .max(1);
+        creature.position = position;
+        creature.home = position;
+        creature.hit_points = hit_points;
+        creature.max_hit_points = hit_points;
+        creature.aware = false;
+        Some(creature)
+    }
+
+    ///A random creature found at a depth
+    pub fn spawn_for_depth(
+        &self,
+        depth: usize,
+        position: Point,
+    ) -> Option<Creature> {
+        let keys = self.keys_for_depth(depth);
+        if keys.is_empty() {
+            return None;
+        }
+        let mut rng = rand::thread_rng();
+        self.spawn(&keys[rng.gen_range(0..keys.len())], position)
+    }
+}
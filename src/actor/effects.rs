@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+
+///Structured rule granted by a trait, such as a race modifier
+#[derive(Clone, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize, Debug)]
+#[serde(tag = "effect", rename_all = "snake_case")]
+pub enum Effect {
+    WeaponProficiency { weapons: Vec<String> },
+    ArmorProficiency { armor: Vec<String> },
+    ToolProficiency { tools: Vec<String> },
+    SkillProficiency { skills: Vec<String> },
+    Resistance { damage: String },
+    Advantage { against: String },
+    Speed { speed: u32 },
+    HitPointsPerLevel { hit_points: u32 },
+    Darkvision { range: u32 },
+}
+
+///Everything granted by a set of effects gathered together
+#[derive(Clone, Debug, Default)]
+pub struct Effects {
+    pub weapon_proficiencies: Vec<String>,
+    pub armor_proficiencies: Vec<String>,
+    pub tool_proficiencies: Vec<String>,
+    pub skill_proficiencies: Vec<String>,
+    pub resistances: Vec<String>,
+    pub advantages: Vec<String>,
+    pub speed: Option<u32>,
+    pub hit_points_per_level: u32,
+    pub darkvision: u32,
+}
+
+impl Effects {
+    pub fn new<'a>(effects: impl Iterator<Item = &'a Effect>) -> Effects {
+        let mut gathered = Effects::default();
+        for effect in effects {
+            gathered.apply(effect);
+        }
+        gathered
+    }
+
+    ///Add what an effect grants, lists are kept free of duplicates and the
+    ///best speed and darkvision win
+    pub fn apply(
+        &mut self,
+        effect: &Effect,
+    ) {
+        fn extend(
+            list: &mut Vec<String>,
+            items: &[String],
+        ) {
+            for item in items.iter() {
+                if !list.contains(item) {
+                    list.push(item.clone());
+                }
+            }
+        }
+
+        match effect {
+            Effect::WeaponProficiency { weapons } => {
+                extend(&mut self.weapon_proficiencies, weapons)
+            }
+            Effect::ArmorProficiency { armor } => extend(&mut self.armor_proficiencies, armor),
+            Effect::ToolProficiency { tools } => extend(&mut self.tool_proficiencies, tools),
+            Effect::SkillProficiency { skills } => extend(&mut self.skill_proficiencies, skills),
+            Effect::Resistance { damage } => {
+                extend(&mut self.resistances, std::slice::from_ref(damage))
+            }
+            Effect::Advantage { against } => {
+                extend(&mut self.advantages, std::slice::from_ref(against))
+            }
+            Effect::Speed { speed } => self.speed = Some(self.speed.unwrap_or(0).max(*speed)),
+            Effect::HitPointsPerLevel { hit_points } => self.hit_points_per_level += hit_points,
+            Effect::Darkvision { range } => self.darkvision = self.darkvision.max(*range),
+        }
+    }
+
+    pub fn is_resistant(
+        &self,
+        damage: &str,
+    ) -> bool {
+        self.resistances
+            .iter()
+            .any(|resistance| resistance == damage)
+    }
+
+    pub fn has_advantage(
+        &self,
+        against: &str,
+    ) -> bool {
+        self.advantages.iter().any(|advantage| advantage == against)
+    }
+}
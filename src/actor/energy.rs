@@ -0,0 +1,95 @@
+use crate::actor::spells::FEET_PER_TILE;
+
+///Seconds in a combat round, the game clock counts seconds
+pub const ROUND_SECONDS: u32 = 6;
+
+///Turns are scheduled in ticks, this many to a second of game time
+pub const TICKS_PER_SECOND: u64 = 60;
+
+///Energy of a plain action such as moving a tile
+///
+///Every second an actor gains its speed in feet as energy, so at 30 ft it
+///acts once a second and crosses its speed in tiles each round
+pub const ACTION_ENERGY: u32 = FEET_PER_TILE * ROUND_SECONDS;
+
+///However slowed, an actor still gets to act now and then
+const MIN_SPEED: u32 = 5;
+
+///Something an actor spends its turn on
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Action {
+    Move,
+    Attack,
+    Cast,
+    ///Using a class feature, quick like a bonus action
+    Feature,
+    Wait,
+    ///Resting for a number of seconds, however fast the actor
+    Rest(u32),
+}
+
+impl Action {
+    ///Energy the action costs, as much as an actor gains over a rest for
+    ///resting
+    pub fn energy(
+        &self,
+        speed: u32,
+    ) -> u32 {
+        match self {
+            Action::Move | Action::Attack | Action::Wait => ACTION_ENERGY,
+            Action::Cast => ACTION_ENERGY * 2,
+            Action::Feature => ACTION_ENERGY / 2,
+            Action::Rest(seconds) => seconds * speed.max(MIN_SPEED),
+        }
+    }
+
+    ///Ticks the action takes an actor moving at a speed in feet
+    pub fn duration(
+        &self,
+        speed: u32,
+    ) -> u64 {
+        self.energy(speed) as u64 * TICKS_PER_SECOND / speed.max(MIN_SPEED) as u64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thirty_feet_moves_once_a_second() {
+        assert_eq!(Action::Move.duration(30), TICKS_PER_SECOND);
+    }
+
+    #[test]
+    fn faster_actors_act_sooner() {
+        assert!(Action::Move.duration(40) < Action::Move.duration(30));
+        assert!(Action::Move.duration(25) > Action::Move.duration(30));
+    }
+
+    #[test]
+    fn actions_cost_in_proportion() {
+        let speed = 30;
+        assert_eq!(
+            Action::Cast.duration(speed),
+            Action::Move.duration(speed) * 2
+        );
+        assert_eq!(
+            Action::Feature.duration(speed),
+            Action::Move.duration(speed) / 2
+        );
+        assert_eq!(Action::Attack.duration(speed), Action::Wait.duration(speed));
+    }
+
+    #[test]
+    fn no_speed_still_acts() {
+        assert_eq!(Action::Move.duration(0), Action::Move.duration(MIN_SPEED));
+    }
+
+    #[test]
+    fn rest_lasts_the_same_at_any_speed() {
+        for speed in [0, 25, 30, 40] {
+            assert_eq!(Action::Rest(60).duration(speed), 60 * TICKS_PER_SECOND);
+        }
+    }
+}
@@ -1,4 +1,10 @@
+pub mod conditions;
+pub mod creatures;
+pub mod effects;
+pub mod energy;
 pub mod player;
+pub mod skills;
+pub mod spells;
 
 use serde::{Deserialize, Serialize};
 
@@ -13,10 +19,68 @@ pub struct Stats {
     pub wisdom: u32,
 }
 
+///Names of the abilities, in the order they are listed
+pub const ABILITIES: [&str; 6] = [
+    "strength",
+    "dexterity",
+    "constitution",
+    "intellect",
+    "wisdom",
+    "charisma",
+];
+
+///Ability name as used by Stats, the data files call intellect intelligence
+pub fn ability_key(name: &str) -> &str {
+    if name == "intelligence" {
+        "intellect"
+    } else {
+        name
+    }
+}
+
+impl Stats {
+    ///Score for an ability by name
+    pub fn ability(
+        &self,
+        ability: &str,
+    ) -> u32 {
+        match ability {
+            "strength" => self.strength,
+            "dexterity" => self.dexterity,
+            "charisma" => self.charisma,
+            "constitution" => self.constitution,
+            "intellect" => self.intellect,
+            "wisdom" => self.wisdom,
+            _ => panic!("Unknown ability '{}'", ability),
+        }
+    }
+
+    ///Mutable score for an ability by name
+    pub fn ability_mut(
+        &mut self,
+        ability: &str,
+    ) -> &mut u32 {
+        match ability {
+            "strength" => &mut self.strength,
+            "dexterity" => &mut self.dexterity,
+            "charisma" => &mut self.charisma,
+            "constitution" => &mut self.constitution,
+            "intellect" => &mut self.intellect,
+            "wisdom" => &mut self.wisdom,
+            _ => panic!("Unknown ability '{}'", ability),
+        }
+    }
+}
+
+///Modifier given by an ability score
+pub fn ability_modifier(score: u32) -> i32 {
+    (score as i32 - 10).div_euclid(2)
+}
+
 ///Enum containing the 9 different alignments
 ///
 ///Defaults to N
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
 pub enum Alignment {
     LG, //Lawful good
     NG, //Neutral good
@@ -33,6 +97,34 @@ impl Default for Alignment {
         Alignment::N
     }
 }
+impl Alignment {
+    ///Every alignment, lawful to chaotic and good to evil
+    pub const ALL: [Alignment; 9] = [
+        Alignment::LG,
+        Alignment::NG,
+        Alignment::CG,
+        Alignment::LN,
+        Alignment::N,
+        Alignment::CN,
+        Alignment::LE,
+        Alignment::NE,
+        Alignment::CE,
+    ];
+
+    pub fn name(&self) -> &str {
+        match self {
+            Alignment::LG => "Lawful Good",
+            Alignment::NG => "Neutral Good",
+            Alignment::CG => "Chaotic Good",
+            Alignment::LN => "Lawful Neutral",
+            Alignment::N => "Neutral",
+            Alignment::CN => "Chaotic Neutral",
+            Alignment::LE => "Lawful Evil",
+            Alignment::NE => "Neutral Evil",
+            Alignment::CE => "Chaotic Evil",
+        }
+    }
+}
 
 ///Enum containing 2 genders
 ///
@@ -0,0 +1,167 @@
+use crate::screen::COLUMN_WIDTH;
+use convert_case::{Case, Casing};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BackgroundFeature {
+    pub feature: String,
+    pub description: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Background {
+    background: String,
+    description: String,
+    skill_proficiencies: Vec<String>,
+    tool_proficiencies: Vec<String>,
+    feature: BackgroundFeature,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Backgrounds {
+    backgrounds: Vec<Background>,
+}
+
+impl Default for Backgrounds {
+    fn default() -> Self {
+        Backgrounds::new()
+    }
+}
+
+impl Backgrounds {
+    pub fn new() -> Backgrounds {
+        let background_json = include_str!("backgrounds.json");
+        let backgrounds: Vec<Background> = serde_json::from_str(background_json).unwrap();
+        Backgrounds { backgrounds }
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.backgrounds
+            .iter()
+            .map(|background| background.background.clone())
+            .collect()
+    }
+
+    fn value(
+        &self,
+        key: &str,
+    ) -> Option<&Background> {
+        self.backgrounds
+            .iter()
+            .find(|background| background.background == key)
+    }
+
+    pub fn background(
+        &self,
+        key: &str,
+    ) -> String {
+        self.value(key).unwrap().background.clone()
+    }
+
+    pub fn description(
+        &self,
+        key: &str,
+    ) -> String {
+        self.value(key).unwrap().description.clone()
+    }
+
+    pub fn skill_proficiencies(
+        &self,
+        key: &str,
+    ) -> Vec<String> {
+        self.value(key).unwrap().skill_proficiencies.clone()
+    }
+
+    pub fn tool_proficiencies(
+        &self,
+        key: &str,
+    ) -> Vec<String> {
+        self.value(key).unwrap().tool_proficiencies.clone()
+    }
+
+    pub fn feature(
+        &self,
+        key: &str,
+    ) -> BackgroundFeature {
+        self.value(key).unwrap().feature.clone()
+    }
+
+    pub fn detail_background(
+        &self,
+        key: &str,
+    ) -> String {
+        format!("{}\n", self.background(key).to_case(Case::Title))
+    }
+
+    fn detail_description(
+        &self,
+        key: &str,
+    ) -> String {
+        format!(
+            "{}\n",
+            textwrap::fill(
+                &self.description(key),
+                textwrap::Options::new(COLUMN_WIDTH)
+                    .initial_indent("  ")
+                    .subsequent_indent("  ")
+            )
+        )
+    }
+
+    ///Indented list under a heading
+    fn detail_list(
+        heading: &str,
+        list: &[String],
+    ) -> String {
+        let mut list_str: String;
+
+        list_str = format!("- {}\n", heading);
+
+        if !list.is_empty() {
+            list_str = format!(
+                "{}{}\n",
+                list_str,
+                textwrap::fill(
+                    &list.join(", ").to_case(Case::Title),
+                    textwrap::Options::new(COLUMN_WIDTH)
+                        .initial_indent("  - ")
+                        .subsequent_indent("    ")
+                )
+            );
+        }
+
+        list_str
+    }
+
+    fn detail_feature(
+        &self,
+        key: &str,
+    ) -> String {
+        let feature = self.feature(key);
+
+        format!(
+            "- Feature: {}\n{}\n",
+            feature.feature.to_case(Case::Title),
+            textwrap::fill(
+                &feature.description,
+                textwrap::Options::new(COLUMN_WIDTH)
+                    .initial_indent("  ")
+                    .subsequent_indent("  ")
+            )
+        )
+    }
+
+    pub fn details(
+        &self,
+        key: &str,
+    ) -> String {
+        [
+            self.detail_background(key),
+            self.detail_description(key),
+            Backgrounds::detail_list("Skill Proficiencies", &self.skill_proficiencies(key)),
+            Backgrounds::detail_list("Tool Proficiencies", &self.tool_proficiencies(key)),
+            self.detail_feature(key),
+        ]
+        .join("\n")
+    }
+}
@@ -2,6 +2,193 @@ use crate::screen::COLUMN_WIDTH;
 use convert_case::{Case, Casing};
 use serde::{Deserialize, Serialize};
 
+///When the uses of a feature come back
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Recharge {
+    ShortRest,
+    LongRest,
+}
+
+///Value that changes with the class level, from the level given on
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct LevelValue {
+    level: u32,
+    value: u32,
+}
+
+///Something a class can do once it reaches a level
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ClassFeature {
+    pub feature: String,
+    pub description: String,
+    pub level: u32,
+    //Uses between rests, unlimited if there are none
+    #[serde(default)]
+    uses: Vec<LevelValue>,
+    #[serde(default)]
+    pub recharge: Option<Recharge>,
+    //Game time the feature lasts once used, zero if it takes effect at once
+    #[serde(default)]
+    pub duration: u32,
+    //Strength of the feature, such as extra damage or dice
+    #[serde(default)]
+    scaling: Vec<LevelValue>,
+}
+
+impl ClassFeature {
+    ///Value for the highest level reached in a table
+    fn value_at(
+        table: &[LevelValue],
+        level: u32,
+    ) -> Option<u32> {
+        table
+            .iter()
+            .rfind(|level_value| level_value.level <= level)
+            .map(|level_value| level_value.value)
+    }
+
+    ///Uses between rests at a level, None if it can be used at will
+    pub fn uses(
+        &self,
+        level: u32,
+    ) -> Option<u32> {
+        ClassFeature::value_at(&self.uses, level)
+    }
+
+    ///Strength of the feature at a level
+    pub fn scaling(
+        &self,
+        level: u32,
+    ) -> u32 {
+        ClassFeature::value_at(&self.scaling, level).unwrap_or(0)
+    }
+
+    ///Used as an action rather than always working
+    pub fn is_active(&self) -> bool {
+        !self.uses.is_empty()
+    }
+}
+
+///Spell slots of each spell level for a full caster, by class level
+const FULL_CASTER_SLOTS: [[u32; 9]; 20] = [
+    [2, 0, 0, 0, 0, 0, 0, 0, 0],
+    [3, 0, 0, 0, 0, 0, 0, 0, 0],
+    [4, 2, 0, 0, 0, 0, 0, 0, 0],
+    [4, 3, 0, 0, 0, 0, 0, 0, 0],
+    [4, 3, 2, 0, 0, 0, 0, 0, 0],
+    [4, 3, 3, 0, 0, 0, 0, 0, 0],
+    [4, 3, 3, 1, 0, 0, 0, 0, 0],
+    [4, 3, 3, 2, 0, 0, 0, 0, 0],
+    [4, 3, 3, 3, 1, 0, 0, 0, 0],
+    [4, 3, 3, 3, 2, 0, 0, 0, 0],
+    [4, 3, 3, 3, 2, 1, 0, 0, 0],
+    [4, 3, 3, 3, 2, 1, 0, 0, 0],
+    [4, 3, 3, 3, 2, 1, 1, 0, 0],
+    [4, 3, 3, 3, 2, 1, 1, 0, 0],
+    [4, 3, 3, 3, 2, 1, 1, 1, 0],
+    [4, 3, 3, 3, 2, 1, 1, 1, 0],
+    [4, 3, 3, 3, 2, 1, 1, 1, 1],
+    [4, 3, 3, 3, 3, 1, 1, 1, 1],
+    [4, 3, 3, 3, 3, 2, 1, 1, 1],
+    [4, 3, 3, 3, 3, 2, 2, 1, 1],
+];
+
+///How quickly a class gains spell slots
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CasterProgression {
+    Full,
+    ///Slots of a full caster of half the level, from level 2
+    Half,
+    ///A few slots all of the highest level, back after a short rest
+    Pact,
+}
+
+///How a class picks the spells it can cast
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpellPreparation {
+    ///Prepared from the whole class list after a long rest, ability modifier plus level
+    Prepared,
+    ///Learned one at a time as the class levels
+    Known,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Spellcasting {
+    pub ability: String,
+    pub progression: CasterProgression,
+    pub preparation: SpellPreparation,
+    #[serde(default)]
+    cantrips: Vec<LevelValue>,
+    //Spells known by a known caster
+    #[serde(default)]
+    known: Vec<LevelValue>,
+}
+
+impl Spellcasting {
+    ///Cantrips known at a level
+    pub fn cantrips(
+        &self,
+        level: u32,
+    ) -> u32 {
+        ClassFeature::value_at(&self.cantrips, level).unwrap_or(0)
+    }
+
+    ///Spells known at a level by a known caster
+    pub fn known(
+        &self,
+        level: u32,
+    ) -> u32 {
+        ClassFeature::value_at(&self.known, level).unwrap_or(0)
+    }
+
+    ///Slots of each spell level at a class level, spell level 1 first
+    pub fn slots(
+        &self,
+        level: u32,
+    ) -> [u32; 9] {
+        let level = level.clamp(1, 20);
+        match self.progression {
+            CasterProgression::Full => FULL_CASTER_SLOTS[level as usize - 1],
+            CasterProgression::Half if level < 2 => [0; 9],
+            CasterProgression::Half => FULL_CASTER_SLOTS[(level as usize).div_ceil(2) - 1],
+            CasterProgression::Pact => {
+                let mut slots = [0; 9];
+                let count = match level {
+                    1 => 1,
+                    2..=10 => 2,
+                    11..=16 => 3,
+                    _ => 4,
+                };
+                slots[level.div_ceil(2).min(5) as usize - 1] = count;
+                slots
+            }
+        }
+    }
+
+    ///Highest level of spell there is a slot for
+    pub fn max_spell_level(
+        &self,
+        level: u32,
+    ) -> u32 {
+        self.slots(level)
+            .iter()
+            .rposition(|&count| count > 0)
+            .map_or(0, |index| index as u32 + 1)
+    }
+
+    ///When spent slots come back
+    pub fn recharge(&self) -> Recharge {
+        if self.progression == CasterProgression::Pact {
+            Recharge::ShortRest
+        } else {
+            Recharge::LongRest
+        }
+    }
+}
+
 #[derive(Debug, Default, Serialize, Deserialize)]
 struct Class {
     class: String,
@@ -11,6 +198,10 @@ struct Class {
     saving_throw_proficiencies: Vec<String>,
     armor_proficiencies: Vec<String>,
     weapon_proficiencies: Vec<String>,
+    #[serde(default)]
+    features: Vec<ClassFeature>,
+    #[serde(default)]
+    spellcasting: Option<Spellcasting>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -18,6 +209,12 @@ pub struct Classes {
     classes: Vec<Class>,
 }
 
+impl Default for Classes {
+    fn default() -> Self {
+        Classes::new()
+    }
+}
+
 impl Classes {
     pub fn new() -> Classes {
         let class_json = include_str!("classes.json");
@@ -94,6 +291,20 @@ impl Classes {
         self.value(key).unwrap().weapon_proficiencies.clone()
     }
 
+    pub fn features(
+        &self,
+        key: &str,
+    ) -> Vec<ClassFeature> {
+        self.value(key).unwrap().features.clone()
+    }
+
+    pub fn spellcasting(
+        &self,
+        key: &str,
+    ) -> Option<Spellcasting> {
+        self.value(key).unwrap().spellcasting.clone()
+    }
+
     pub fn detail_class(
         &self,
         key: &str,
@@ -230,6 +441,56 @@ impl Classes {
         weapon_proficiencies_str
     }
 
+    fn detail_features(
+        &self,
+        key: &str,
+    ) -> String {
+        let mut features_str: String;
+
+        features_str = "- Features\n".to_string();
+
+        for feature in self.features(key).iter() {
+            features_str = format!(
+                "{}  - {}, level {}\n{}\n",
+                features_str,
+                feature.feature.to_case(Case::Title),
+                feature.level,
+                textwrap::fill(
+                    &feature.description,
+                    textwrap::Options::new(COLUMN_WIDTH)
+                        .initial_indent("    ")
+                        .subsequent_indent("    ")
+                )
+            );
+        }
+
+        features_str
+    }
+
+    fn detail_spellcasting(
+        &self,
+        key: &str,
+    ) -> String {
+        let mut spellcasting_str: String;
+
+        spellcasting_str = "- Spellcasting\n".to_string();
+
+        match self.spellcasting(key) {
+            Some(spellcasting) => {
+                spellcasting_str = format!(
+                    "{}  - {} caster, {} spells, cast with {}\n",
+                    spellcasting_str,
+                    format!("{:?}", spellcasting.progression).to_case(Case::Title),
+                    format!("{:?}", spellcasting.preparation).to_lowercase(),
+                    spellcasting.ability.to_case(Case::Title)
+                );
+            }
+            None => spellcasting_str = format!("{}  - None\n", spellcasting_str),
+        }
+
+        spellcasting_str
+    }
+
     pub fn details(
         &self,
         key: &str,
@@ -242,7 +503,78 @@ impl Classes {
             self.detail_saving_throw_proficiencies(key),
             self.detail_armor_proficiencies(key),
             self.detail_weapon_proficiencies(key),
+            self.detail_features(key),
+            self.detail_spellcasting(key),
         ]
         .join("\n")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slots(
+        class: &str,
+        level: u32,
+    ) -> [u32; 9] {
+        Classes::new().spellcasting(class).unwrap().slots(level)
+    }
+
+    #[test]
+    fn only_casters_have_spellcasting() {
+        let classes = Classes::new();
+        assert!(classes.spellcasting("fighter").is_none());
+        assert!(classes.spellcasting("barbarian").is_none());
+        assert!(classes.spellcasting("wizard").is_some());
+    }
+
+    #[test]
+    fn full_caster_gains_a_spell_level_every_other_level() {
+        assert_eq!(slots("wizard", 1), [2, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(slots("wizard", 3), [4, 2, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(slots("wizard", 5), [4, 3, 2, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(slots("wizard", 20), [4, 3, 3, 3, 3, 2, 2, 1, 1]);
+        let wizard = Classes::new().spellcasting("wizard").unwrap();
+        let levels: Vec<u32> = (1..=20)
+            .map(|level| wizard.max_spell_level(level))
+            .collect();
+        assert!(levels.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(wizard.max_spell_level(17), 9);
+    }
+
+    #[test]
+    fn half_caster_starts_at_level_two_and_follows_half_its_level() {
+        assert_eq!(slots("paladin", 1), [0; 9]);
+        assert_eq!(slots("paladin", 2), slots("wizard", 1));
+        assert_eq!(slots("paladin", 5), slots("wizard", 3));
+        assert_eq!(slots("paladin", 20), slots("wizard", 10));
+    }
+
+    #[test]
+    fn pact_slots_are_all_of_the_highest_level() {
+        assert_eq!(slots("warlock", 1), [1, 0, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(slots("warlock", 3), [0, 2, 0, 0, 0, 0, 0, 0, 0]);
+        assert_eq!(slots("warlock", 11), [0, 0, 0, 0, 3, 0, 0, 0, 0]);
+        assert_eq!(slots("warlock", 20), [0, 0, 0, 0, 4, 0, 0, 0, 0]);
+    }
+
+    #[test]
+    fn levels_out_of_range_are_clamped() {
+        assert_eq!(slots("wizard", 0), slots("wizard", 1));
+        assert_eq!(slots("wizard", 25), slots("wizard", 20));
+    }
+
+    #[test]
+    fn pact_slots_come_back_after_a_short_rest() {
+        let classes = Classes::new();
+        assert_eq!(
+            classes.spellcasting("warlock").unwrap().recharge(),
+            Recharge::ShortRest
+        );
+        assert_eq!(
+            classes.spellcasting("cleric").unwrap().recharge(),
+            Recharge::LongRest
+        );
+    }
+}
@@ -1,10 +1,10 @@
+use crate::actor::player::backgrounds::Backgrounds;
 use crate::actor::player::classes::Classes;
 use crate::actor::player::races::Races;
 use crate::actor::player::Character;
-use crate::actor::{Alignment, Gender};
+use crate::actor::{ability_key, Alignment, Gender, Stats, ABILITIES};
 use crate::items::weapons::Weapons;
 use crate::screen::Screen;
-use crate::screen::COLUMN_WIDTH;
 use crate::utils::*;
 use convert_case::{Case, Casing};
 use sm::sm;
@@ -14,31 +14,43 @@ sm! {
         InitialStates { Idle }
 
         ChooseGender {
-            Idle, Summary, Gender => Gender
+            Idle, Summary, Gender, Race => Gender
         }
 
         ChooseRace {
-            Gender, Race => Race
+            Gender, Class => Race
         }
 
         ChooseClass {
-            Race, Class => Class
+            Race, Stats => Class
         }
 
         ChooseStats {
-            Class => Stats
+            Class, Stats, Equipment => Stats
         }
 
         ChooseEquipment {
-            Stats, Equipment => Equipment
+            Stats, Background => Equipment
+        }
+
+        ChooseBackground {
+            Equipment, Name => Background
         }
 
         ChooseName {
-            Equipment, Name => Name
+            Background, Name, Alignment => Name
+        }
+
+        ChooseAlignment {
+            Name, Age => Alignment
+        }
+
+        ChooseAge {
+            Alignment, Age, Summary => Age
         }
 
         ChooseSummary {
-            Name => Summary
+            Age => Summary
         }
 
         Done {
@@ -61,10 +73,287 @@ fn roll_stats(rolls: &mut Vec<u32>) {
     rolls.reverse();
 }
 
+///Scores handed out by the standard array
+const STANDARD_ARRAY: [u32; 6] = [15, 14, 13, 12, 10, 8];
+
+///Points to spend when buying scores
+const POINT_BUY_POINTS: u32 = 27;
+
+///Cost of each score that can be bought
+const POINT_BUY_COSTS: [(u32, u32); 8] = [
+    (8, 0),
+    (9, 1),
+    (10, 2),
+    (11, 3),
+    (12, 4),
+    (13, 5),
+    (14, 7),
+    (15, 9),
+];
+
+///Cost of buying a score, None if it can not be bought
+fn point_buy_cost(score: u32) -> Option<u32> {
+    POINT_BUY_COSTS
+        .iter()
+        .find(|(cost_score, _)| *cost_score == score)
+        .map(|(_, cost)| *cost)
+}
+
+///Ability scores in one line
+fn abilities_summary(abilities: &Stats) -> String {
+    format!(
+        "Str:{:>2} Dex:{:>2} Cha:{:>2} Con:{:>2} Int:{:>2} Wis:{:>2}",
+        &abilities.strength,
+        &abilities.dexterity,
+        &abilities.charisma,
+        &abilities.constitution,
+        &abilities.intellect,
+        &abilities.wisdom
+    )
+}
+
+///Base scores and the scores with race bonuses added
+fn abilities_preview(
+    base: &Stats,
+    bonus: &Stats,
+) -> String {
+    format!(
+        "Base scores\n  {}\n\nWith race bonuses\n  {}",
+        abilities_summary(base),
+        abilities_summary(&add_abilities(base, bonus))
+    )
+}
+
+///Add two sets of scores together
+fn add_abilities(
+    a: &Stats,
+    b: &Stats,
+) -> Stats {
+    let mut abilities = a.clone();
+    for ability in ABILITIES.iter() {
+        *abilities.ability_mut(ability) += b.ability(ability);
+    }
+    abilities
+}
+
+///Every ability, the class primary abilities first, then constitution
+fn recommended_abilities(
+    classes: &Classes,
+    class: &str,
+) -> Vec<&'static str> {
+    let mut recommended: Vec<&'static str> = Vec::new();
+    for primary_ability in classes.primary_ability(class).iter() {
+        //Entries may offer a choice such as "strength or dexterity", take the first
+        let primary_ability = ability_key(primary_ability.split(" or ").next().unwrap_or(""));
+        if let Some(ability) = ABILITIES
+            .iter()
+            .find(|&&ability| ability == primary_ability)
+        {
+            if !recommended.contains(ability) {
+                recommended.push(ability);
+            }
+        }
+    }
+    for ability in [
+        "constitution",
+        "dexterity",
+        "wisdom",
+        "strength",
+        "intellect",
+        "charisma",
+    ]
+    .iter()
+    {
+        if !recommended.contains(ability) {
+            recommended.push(ability);
+        }
+    }
+    recommended
+}
+
+///Hand out scores, highest first, in the recommended order
+fn recommended_stats(
+    stats: &[u32],
+    recommended: &[&str],
+) -> Stats {
+    let mut stats = stats.to_vec();
+    stats.sort_unstable_by(|a, b| b.cmp(a));
+    let mut base = Stats::default();
+    for (ability, stat) in recommended.iter().zip(stats.iter()) {
+        *base.ability_mut(ability) = *stat;
+    }
+    base
+}
+
+///Assign each score to an ability by hand
+///
+///Backing out undoes the last assignment, or gives up once nothing is assigned
+fn assign_by_hand(
+    screen: &mut Screen,
+    mut stats: Vec<u32>,
+    bonus: &Stats,
+) -> Option<Stats> {
+    let mut base = Stats::default();
+    let mut abilities: Vec<&str> = ABILITIES.to_vec();
+    //Assignments so far, undone by backing out
+    let mut assigned: Vec<(&str, u32)> = Vec::new();
+
+    while !abilities.is_empty() {
+        let preview = format!(
+            "{}\n\nScores left to assign {:?}",
+            abilities_preview(&base, bonus),
+            stats
+        );
+
+        let ability_names: Vec<String> = abilities
+            .iter()
+            .map(|ability| ability.to_case(Case::Title))
+            .collect();
+        let number = match screen.draw_menu(
+            "Choose ability to assign a score to",
+            &ability_names,
+            &|_| preview.clone(),
+        ) {
+            Some(number) => number,
+            None => {
+                let (ability, stat) = assigned.pop()?;
+                *base.ability_mut(ability) = 0;
+                abilities.push(ability);
+                stats.push(stat);
+                stats.sort_unstable_by(|a, b| b.cmp(a));
+                continue;
+            }
+        };
+        let ability = abilities[number];
+
+        let stat_names: Vec<String> = stats.iter().map(|stat| stat.to_string()).collect();
+        if let Some(stat_number) = screen.draw_menu(
+            &format!("Choose score for {}", ability.to_case(Case::Title)),
+            &stat_names,
+            &|_| preview.clone(),
+        ) {
+            let stat = stats.remove(stat_number);
+            abilities.remove(number);
+            *base.ability_mut(ability) = stat;
+            assigned.push((ability, stat));
+        }
+    }
+    Some(base)
+}
+
+///Buy scores from 8 to 15 with a pool of points
+fn point_buy(
+    screen: &mut Screen,
+    recommended: &[&str],
+    bonus: &Stats,
+) -> Option<Stats> {
+    let mut base = Stats::default();
+    for ability in ABILITIES.iter() {
+        *base.ability_mut(ability) = 8;
+    }
+    let costs = POINT_BUY_COSTS
+        .iter()
+        .map(|(score, cost)| format!("{}: {}", score, cost))
+        .collect::<Vec<String>>()
+        .join(", ");
+
+    loop {
+        let spent: u32 = ABILITIES
+            .iter()
+            .map(|ability| point_buy_cost(base.ability(ability)).unwrap())
+            .sum();
+        let left = POINT_BUY_POINTS - spent;
+        let preview = format!(
+            "{}\n\nCost of each score\n  {}",
+            abilities_preview(&base, bonus),
+            costs
+        );
+
+        let mut items: Vec<String> = ABILITIES
+            .iter()
+            .map(|ability| {
+                format!(
+                    "{:<13}{:>2}",
+                    ability.to_case(Case::Title),
+                    base.ability(ability)
+                )
+            })
+            .collect();
+        items.push("Use recommended".to_string());
+        items.push("Done".to_string());
+
+        match screen.draw_menu(
+            &format!("Point buy, {} of {} points left", left, POINT_BUY_POINTS),
+            &items,
+            &|_| preview.clone(),
+        )? {
+            number if number < ABILITIES.len() => {
+                let ability = ABILITIES[number];
+                let current_cost = point_buy_cost(base.ability(ability)).unwrap();
+                //Only offer scores that can be afforded
+                let scores: Vec<(u32, u32)> = POINT_BUY_COSTS
+                    .iter()
+                    .filter(|(_, cost)| *cost <= left + current_cost)
+                    .copied()
+                    .collect();
+                let score_names: Vec<String> = scores
+                    .iter()
+                    .map(|(score, cost)| format!("{:>2} costs {}", score, cost))
+                    .collect();
+                if let Some(score_number) = screen.draw_menu(
+                    &format!("Choose score for {}", ability.to_case(Case::Title)),
+                    &score_names,
+                    &|_| preview.clone(),
+                ) {
+                    *base.ability_mut(ability) = scores[score_number].0;
+                }
+            }
+            //The standard array costs exactly the points available
+            number if number == ABILITIES.len() => {
+                base = recommended_stats(&STANDARD_ARRAY, recommended);
+            }
+            _ => return Some(base),
+        }
+    }
+}
+
+///Check the final scores are the base scores plus the race bonuses, and that
+///the base scores are in range for how they were generated
+fn validate_abilities(
+    base: &Stats,
+    bonus: &Stats,
+    bought: bool,
+) -> Result<(), String> {
+    let abilities = add_abilities(base, bonus);
+    let mut points = 0;
+    for ability in ABILITIES.iter() {
+        let name = ability.to_case(Case::Title);
+        if bought {
+            match point_buy_cost(base.ability(ability)) {
+                Some(cost) => points += cost,
+                None => return Err(format!("{} must be bought between 8 and 15", name)),
+            }
+        } else if !(3..=18).contains(&base.ability(ability)) {
+            return Err(format!("{} must be between 3 and 18", name));
+        }
+        if abilities.ability(ability) > 20 {
+            return Err(format!("{} can not be over 20", name));
+        }
+    }
+    if points > POINT_BUY_POINTS {
+        return Err(format!(
+            "{} points spent, only {} are available",
+            points, POINT_BUY_POINTS
+        ));
+    }
+    Ok(())
+}
+
 pub fn generate(
     screen: &mut Screen,
     races: &Races,
     classes: &Classes,
+    backgrounds: &Backgrounds,
     weapons: &Weapons,
 ) -> Character {
     let mut character: Character = Character {
@@ -84,299 +373,196 @@ pub fn generate(
             }
             GenderByChooseGender(m) => {
                 screen.set_header("Character Creation - Gender");
-                let msg = "\n\
-                                     1) Male\n\
-                                     2) Female";
-                screen.set_msg(msg);
-
-                let number =
-                    screen.draw_pick_a_number("Choose number, leave blank for random.", 1, 2) - 1;
 
-                screen.set_msg(&format!(
-                    "{}\n\n{}",
-                    msg,
-                    if number == 0 { "M" } else { "F" }
-                ));
-
-                if screen.draw_pick_yes_or_no("Use this gender?") {
-                    character.gender = if number == 0 { Gender::M } else { Gender::F };
-                    m.transition(ChooseRace).as_enum()
-                } else {
-                    m.transition(ChooseGender).as_enum()
+                let genders = vec!["Male".to_string(), "Female".to_string()];
+                match screen.draw_menu("Choose gender", &genders, &|_| "".to_string()) {
+                    Some(number) => {
+                        character.gender = if number == 0 { Gender::M } else { Gender::F };
+                        m.transition(ChooseRace).as_enum()
+                    }
+                    None => m.transition(ChooseGender).as_enum(),
                 }
             }
             RaceByChooseRace(m) => {
                 screen.set_header("Character Creation - Race");
 
-                let mut msg: String = "".to_string();
-                for (count, race_key) in races.keys().iter().enumerate() {
-                    msg = format!(
-                        "{}\n{:>2}) {}",
-                        msg,
-                        count + 1,
-                        &races.detail_race(&race_key).trim()
-                    );
-                }
-                screen.set_msg(&msg.strip_prefix('\n').unwrap());
-
-                let number = screen.draw_pick_a_number(
-                    "Choose race, leave blank for random.",
-                    1,
-                    races.keys().len() as u32,
-                ) - 1;
-
-                screen.set_msg(&races.details(&races.keys()[number as usize]));
-
-                if screen.draw_pick_yes_or_no("Use this race?") {
-                    character.race = races.keys()[number as usize].to_string();
-                    m.transition(ChooseClass).as_enum()
-                } else {
-                    m.transition(ChooseRace).as_enum()
+                let keys = races.keys();
+                let names: Vec<String> = keys
+                    .iter()
+                    .map(|key| races.detail_race(key).trim().to_string())
+                    .collect();
+                match screen.draw_menu("Choose race", &names, &|index| races.details(&keys[index]))
+                {
+                    Some(number) => {
+                        character.race = keys[number].to_string();
+                        m.transition(ChooseClass).as_enum()
+                    }
+                    None => m.transition(ChooseGender).as_enum(),
                 }
             }
             ClassByChooseClass(m) => {
                 screen.set_header("Character Creation - Class");
 
-                let mut msg: String = "".to_string();
-                for (count, class_key) in classes.keys().iter().enumerate() {
-                    msg = format!(
-                        "{}\n{:>2}) {}",
-                        msg,
-                        count + 1,
-                        &classes.detail_class(&class_key).trim()
-                    );
-                }
-                screen.set_msg(&msg.strip_prefix('\n').unwrap());
-
-                let number = screen.draw_pick_a_number(
-                    "Choose class, leave blank for random.",
-                    1,
-                    classes.keys().len() as u32,
-                ) - 1;
-
-                screen.set_msg(&classes.details(&classes.keys()[number as usize]));
-
-                if screen.draw_pick_yes_or_no("Use this class?") {
-                    character.class = classes.keys()[number as usize].to_string();
-                    m.transition(ChooseStats).as_enum()
-                } else {
-                    m.transition(ChooseClass).as_enum()
+                let keys = classes.keys();
+                let names: Vec<String> = keys
+                    .iter()
+                    .map(|key| classes.detail_class(key).trim().to_string())
+                    .collect();
+                match screen.draw_menu("Choose class", &names, &|index| {
+                    classes.details(&keys[index])
+                }) {
+                    Some(number) => {
+                        character.class = keys[number].to_string();
+                        m.transition(ChooseStats).as_enum()
+                    }
+                    None => m.transition(ChooseRace).as_enum(),
                 }
             }
             StatsByChooseStats(m) => {
                 screen.set_header("Character Creation - Abilities");
 
-                let mut msg: String;
-
-                //Add in race bonuses for abilities
-                character.abilities.strength = races
-                    .ability_score_increase(&character.race)
-                    .abilities
-                    .strength;
-                character.abilities.dexterity = races
-                    .ability_score_increase(&character.race)
-                    .abilities
-                    .dexterity;
-                character.abilities.charisma = races
-                    .ability_score_increase(&character.race)
-                    .abilities
-                    .charisma;
-                character.abilities.constitution = races
-                    .ability_score_increase(&character.race)
-                    .abilities
-                    .constitution;
-                character.abilities.intellect = races
-                    .ability_score_increase(&character.race)
-                    .abilities
-                    .intellect;
-                character.abilities.wisdom = races
-                    .ability_score_increase(&character.race)
-                    .abilities
-                    .wisdom;
-
-                msg = format!(
-                    "Str:{:>2} Dex:{:>2} Cha:{:>2} Con:{:>2} Int:{:>2} Wis:{:>2}\n",
-                    &character.abilities.strength,
-                    &character.abilities.dexterity,
-                    &character.abilities.charisma,
-                    &character.abilities.constitution,
-                    &character.abilities.intellect,
-                    &character.abilities.wisdom
+                let bonus = races.ability_score_increase(&character.race).abilities;
+                let recommended = recommended_abilities(classes, &character.class);
+                let recommended_str = format!(
+                    "Recommended for a {}, best first\n  {}",
+                    classes.class(&character.class).to_case(Case::Title),
+                    recommended
+                        .iter()
+                        .map(|ability| ability.to_case(Case::Title))
+                        .collect::<Vec<String>>()
+                        .join(", ")
                 );
 
-                println!("Roll stats:\n");
-                msg = format!("{}\n\nYou can choose to use the default stat values or to roll random new ones.",msg);
-
-                let mut stats: Vec<u32> = vec![15, 14, 13, 12, 10, 8];
-                msg = format!("{}\n\nThe default stats are {:?}", msg, stats);
-
-                screen.set_msg(&msg);
-
-                if screen.draw_pick_yes_or_no("Roll your own stats?") {
-                    roll_stats(&mut stats);
-                }
-
-                screen.set_msg(&format!("{}\n\nUsing stats {:?}", msg, stats));
-                screen.draw_display();
-
-                let mut abilities: Vec<&str> = vec![
-                    "strength",
-                    "dexterity",
-                    "charisma",
-                    "constitution",
-                    "intellect",
-                    "wisdom",
+                let modes = vec![
+                    "Standard array".to_string(),
+                    "Roll 4d6, drop lowest".to_string(),
+                    "Point buy".to_string(),
                 ];
-
-                while !abilities.is_empty() {
-                    msg = format!(
-                        "Str:{:>2} Dex:{:>2} Cha:{:>2} Con:{:>2} Int:{:>2} Wis:{:>2}\n",
-                        &character.abilities.strength,
-                        &character.abilities.dexterity,
-                        &character.abilities.charisma,
-                        &character.abilities.constitution,
-                        &character.abilities.intellect,
-                        &character.abilities.wisdom
-                    );
-
-                    msg = format!("{}\n\nChoose ability to assign stat to:", msg);
-
-                    for (count, ability) in abilities.iter().enumerate() {
-                        msg = format!(
-                            "{}\n{:>2}) {}",
-                            msg,
-                            count + 1,
-                            ability.to_case(Case::Title)
-                        );
-                    }
-
-                    screen.set_msg(&msg);
-
-                    let number = screen.draw_pick_a_number(
-                        "Choose ability, leave blank for random.",
-                        1,
-                        abilities.len() as u32,
-                    ) - 1;
-
-                    let ability = abilities[number as usize];
-
-                    abilities.remove(number as usize);
-
-                    msg = format!(
-                        "Str:{:>2} Dex:{:>2} Cha:{:>2} Con:{:>2} Int:{:>2} Wis:{:>2}\n",
-                        &character.abilities.strength,
-                        &character.abilities.dexterity,
-                        &character.abilities.charisma,
-                        &character.abilities.constitution,
-                        &character.abilities.intellect,
-                        &character.abilities.wisdom
-                    );
-                    msg = format!(
-                        "{}\n\n{}\n\nChoose stat value {:?}",
-                        msg,
-                        ability.to_case(Case::Title),
-                        stats
-                    );
-                    for (count, stat) in stats.iter().enumerate() {
-                        msg = format!("{}\n{:>2}) {}", msg, count + 1, stat);
-                    }
-                    screen.set_msg(&msg);
-
-                    let number = screen.draw_pick_a_number(
-                        "Choose stat, leave blank for random.",
-                        1,
-                        stats.len() as u32,
-                    ) - 1;
-
-                    let stat = stats[number as usize];
-
-                    stats.remove(number as usize);
-
-                    if ability == "strength" {
-                        character.abilities.strength += stat;
+                let mode =
+                    screen.draw_menu("Choose how to generate abilities", &modes, &|number| {
+                        match number {
+                            0 => format!("Assign the scores {:?}", STANDARD_ARRAY),
+                            1 => "Roll 4d6 for each of six scores, dropping the lowest die, \
+                              then assign them"
+                                .to_string(),
+                            _ => format!(
+                                "Spend {} points buying scores from 8 to 15",
+                                POINT_BUY_POINTS
+                            ),
+                        }
+                        .to_string()
+                            + "\n\n"
+                            + &recommended_str
+                    });
+
+                let base = match mode {
+                    None => None,
+                    Some(2) => point_buy(screen, &recommended, &bonus),
+                    Some(number) => {
+                        let mut stats: Vec<u32> = STANDARD_ARRAY.to_vec();
+                        if number == 1 {
+                            roll_stats(&mut stats);
+                        }
+                        let assignments =
+                            vec!["Recommended".to_string(), "Assign by hand".to_string()];
+                        match screen.draw_menu(
+                            &format!("Assign the scores {:?}", stats),
+                            &assignments,
+                            &|number| {
+                                if number == 0 {
+                                    abilities_preview(
+                                        &recommended_stats(&stats, &recommended),
+                                        &bonus,
+                                    ) + "\n\n"
+                                        + &recommended_str
+                                } else {
+                                    "Choose which ability gets each score".to_string()
+                                }
+                            },
+                        ) {
+                            Some(0) => Some(recommended_stats(&stats, &recommended)),
+                            Some(_) => assign_by_hand(screen, stats, &bonus),
+                            None => None,
+                        }
                     }
-                    if ability == "dexterity" {
-                        character.abilities.dexterity += stat;
-                    }
-                    if ability == "charisma" {
-                        character.abilities.charisma += stat;
-                    }
-                    if ability == "constitution" {
-                        character.abilities.constitution += stat;
-                    }
-                    if ability == "intellect" {
-                        character.abilities.intellect += stat;
-                    }
-                    if ability == "wisdom" {
-                        character.abilities.wisdom += stat;
+                };
+
+                match base {
+                    //Backing out of the first menu goes back to the class
+                    None if mode.is_none() => m.transition(ChooseClass).as_enum(),
+                    None => m.transition(ChooseStats).as_enum(),
+                    Some(base) => {
+                        character.abilities = add_abilities(&base, &bonus);
+                        match validate_abilities(&base, &bonus, mode == Some(2)) {
+                            Ok(()) => m.transition(ChooseEquipment).as_enum(),
+                            Err(e) => {
+                                screen.set_msg(&e);
+                                screen.draw_display();
+                                m.transition(ChooseStats).as_enum()
+                            }
+                        }
                     }
                 }
-
-                msg = format!(
-                    "Str:{:>2} Dex:{:>2} Cha:{:>2} Con:{:>2} Int:{:>2} Wis:{:>2}\n",
-                    &character.abilities.strength,
-                    &character.abilities.dexterity,
-                    &character.abilities.charisma,
-                    &character.abilities.constitution,
-                    &character.abilities.intellect,
-                    &character.abilities.wisdom
-                );
-                screen.set_msg(&msg);
-                screen.draw_display();
-
-                m.transition(ChooseEquipment).as_enum()
             }
             EquipmentByChooseEquipment(m) => {
-                let mut msg: String;
-
                 screen.set_header("Character Creation - Equipment - Weapon");
 
-                msg = "Choose equipment:".to_string();
-
-                let mut weapon_list: String = "".to_string();
-                for (count, weapon_key) in weapons.keys().iter().enumerate() {
-                    weapon_list = format!(
-                        "{}{:>2}) {} {}\n",
-                        weapon_list,
-                        count + 1,
-                        if character.is_weapon_proficient(races, classes, weapons, weapon_key) {
-                            "*"
-                        } else {
-                            " "
-                        },
-                        weapons.value(&weapon_key).unwrap().detail_name().trim()
-                    );
+                let keys = weapons.keys();
+                let names: Vec<String> = keys
+                    .iter()
+                    .map(|key| {
+                        format!(
+                            "{} {}",
+                            if character.is_weapon_proficient(races, classes, weapons, key) {
+                                "*"
+                            } else {
+                                " "
+                            },
+                            weapons.value(key).unwrap().detail_name().trim()
+                        )
+                    })
+                    .collect();
+                match screen.draw_menu("Choose weapon, * is proficient", &names, &|index| {
+                    weapons.value(&keys[index]).unwrap().details()
+                }) {
+                    Some(number) => {
+                        character.weapons = vec![weapons.weapon(&keys[number]).unwrap()];
+                        m.transition(ChooseBackground).as_enum()
+                    }
+                    None => m.transition(ChooseStats).as_enum(),
                 }
-                msg = format!(
-                    "{}\n{}",
-                    msg,
-                    textwrap::wrap_columns(&weapon_list, 3, COLUMN_WIDTH, "", "", "").join("\n")
-                );
-
-                screen.set_msg(&msg);
-
-                let number = screen.draw_pick_a_number(
-                    "Choose weapon, leave blank for random.",
-                    1,
-                    weapons.keys().len() as u32,
-                ) - 1;
-
-                screen.set_msg(
-                    &weapons
-                        .value(&weapons.keys()[number as usize])
-                        .unwrap()
-                        .details(),
-                );
-
-                if screen.draw_pick_yes_or_no("Use this weapon?") {
-                    character.weapons.push(
-                        weapons
-                            .weapon(&weapons.keys()[number as usize].to_string())
-                            .unwrap(),
-                    );
-                    m.transition(ChooseName).as_enum()
-                } else {
-                    m.transition(ChooseEquipment).as_enum()
+            }
+            BackgroundByChooseBackground(m) => {
+                screen.set_header("Character Creation - Background");
+
+                let keys = backgrounds.keys();
+                let names: Vec<String> = keys
+                    .iter()
+                    .map(|key| backgrounds.detail_background(key).trim().to_string())
+                    .collect();
+                match screen.draw_menu("Choose background", &names, &|index| {
+                    backgrounds.details(&keys[index])
+                }) {
+                    Some(number) => {
+                        let key = &keys[number];
+                        character.background = key.to_string();
+                        //Race modifiers grant proficiencies on top of the background
+                        let effects = races.effects(&character.race);
+                        character.skill_proficiencies = backgrounds.skill_proficiencies(key);
+                        character.tool_proficiencies = backgrounds.tool_proficiencies(key);
+                        for skill in effects.skill_proficiencies {
+                            if !character.skill_proficiencies.contains(&skill) {
+                                character.skill_proficiencies.push(skill);
+                            }
+                        }
+                        for tool in effects.tool_proficiencies {
+                            if !character.tool_proficiencies.contains(&tool) {
+                                character.tool_proficiencies.push(tool);
+                            }
+                        }
+                        m.transition(ChooseName).as_enum()
+                    }
+                    None => m.transition(ChooseEquipment).as_enum(),
                 }
             }
             NameByChooseName(m) => {
@@ -393,21 +579,99 @@ pub fn generate(
                     name = races.generate_name(&character.race, character.gender.clone());
                 }
 
-                screen.set_msg(&name);
+                let choices = vec![
+                    "Use this name".to_string(),
+                    "Choose another name".to_string(),
+                ];
+                match screen.draw_menu(&format!("Name: {}", name), &choices, &|_| "".to_string()) {
+                    Some(0) => {
+                        character.name = name;
+                        m.transition(ChooseAlignment).as_enum()
+                    }
+                    Some(_) => m.transition(ChooseName).as_enum(),
+                    None => m.transition(ChooseBackground).as_enum(),
+                }
+            }
+            AlignmentByChooseAlignment(m) => {
+                screen.set_header("Character Creation - Alignment");
+
+                //The race's typical alignment comes first as the default
+                let typical = races.alignment(&character.race).alignment;
+                let mut alignments = vec![typical];
+                alignments.extend(
+                    Alignment::ALL
+                        .iter()
+                        .filter(|&&alignment| alignment != typical),
+                );
+                let names: Vec<String> = alignments
+                    .iter()
+                    .map(|alignment| {
+                        if *alignment == typical {
+                            format!("{} (typical)", alignment.name())
+                        } else {
+                            alignment.name().to_string()
+                        }
+                    })
+                    .collect();
+                let race_alignment = races.detail_alignment(&character.race);
+                match screen.draw_menu("Choose alignment", &names, &|_| race_alignment.clone()) {
+                    Some(number) => {
+                        character.alignment = alignments[number];
+                        m.transition(ChooseAge).as_enum()
+                    }
+                    None => m.transition(ChooseName).as_enum(),
+                }
+            }
+            AgeByChooseAge(m) => {
+                screen.set_header("Character Creation - Age");
 
-                if screen.draw_pick_yes_or_no("Use this name?") {
-                    character.name = name;
-                    m.transition(ChooseSummary).as_enum()
+                let race_age = races.age(&character.race);
+                screen.set_msg(&format!(
+                    "{}\nType in an age from {} to {}, or leave blank for a random one",
+                    races.detail_age(&character.race),
+                    race_age.adulthood,
+                    race_age.lifespan
+                ));
+                let age_str = screen.draw_enter_string("Enter age:\n");
+                let age_str = age_str.trim();
+
+                let age = if age_str.is_empty() {
+                    Some(races.generate_age(&character.race))
                 } else {
-                    m.transition(ChooseName).as_enum()
+                    age_str
+                        .parse::<u32>()
+                        .ok()
+                        .filter(|age| (race_age.adulthood..=race_age.lifespan).contains(age))
+                };
+
+                match age {
+                    Some(age) => {
+                        let choices =
+                            vec!["Use this age".to_string(), "Choose another age".to_string()];
+                        match screen
+                            .draw_menu(&format!("Age: {}", age), &choices, &|_| "".to_string())
+                        {
+                            Some(0) => {
+                                character.age = age;
+                                m.transition(ChooseSummary).as_enum()
+                            }
+                            Some(_) => m.transition(ChooseAge).as_enum(),
+                            None => m.transition(ChooseAlignment).as_enum(),
+                        }
+                    }
+                    None => {
+                        screen.set_msg(&format!(
+                            "'{}' is not an age from {} to {}",
+                            age_str, race_age.adulthood, race_age.lifespan
+                        ));
+                        screen.draw_display();
+                        m.transition(ChooseAge).as_enum()
+                    }
                 }
             }
             SummaryByChooseSummary(m) => {
                 let mut msg: String = "".to_string();
 
-                character.age = 25;
-                character.alignment = Alignment::N;
-
                 screen.set_header("Character Creation - Summary");
 
                 msg = format!("{}Name: {}\n\n", msg, &character.name);
@@ -426,7 +690,24 @@ pub fn generate(
                     &classes.detail_class(&character.class).trim()
                 );
 
-                msg = format!("{}Alignment: {:?}\n\n", msg, &character.alignment);
+                msg = format!(
+                    "{}Background: {}\n\n",
+                    msg,
+                    &backgrounds.detail_background(&character.background).trim()
+                );
+
+                msg = format!(
+                    "{}Skills: {}\n\n",
+                    msg,
+                    character
+                        .skill_proficiencies
+                        .join(", ")
+                        .to_case(Case::Title)
+                );
+
+                msg = format!("{}Alignment: {}\n\n", msg, character.alignment.name());
+
+                msg = format!("{}Age: {}\n\n", msg, character.age);
 
                 msg = format!(
                     "{}Str:{:>2} Dex:{:>2} Cha:{:>2} Con:{:>2} Int:{:>2} Wis:{:>2}\n\n",
@@ -447,12 +728,11 @@ pub fn generate(
 
                 msg = format!("{}Armor: {}\n\n", msg, "-");
 
-                screen.set_msg(&msg);
-
-                if screen.draw_pick_yes_or_no("Use this character?") {
-                    m.transition(Done).as_enum()
-                } else {
-                    m.transition(ChooseGender).as_enum()
+                let choices = vec!["Use this character".to_string(), "Start over".to_string()];
+                match screen.draw_menu("Character summary", &choices, &|_| msg.clone()) {
+                    Some(0) => m.transition(Done).as_enum(),
+                    Some(_) => m.transition(ChooseGender).as_enum(),
+                    None => m.transition(ChooseAge).as_enum(),
                 }
             }
             FinishedByDone(_) => {
@@ -463,3 +743,80 @@ pub fn generate(
     }
     character
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    ///Scores in the order the abilities are listed
+    fn stats(scores: [u32; 6]) -> Stats {
+        let mut stats = Stats::default();
+        for (ability, score) in ABILITIES.iter().zip(scores.iter()) {
+            *stats.ability_mut(ability) = *score;
+        }
+        stats
+    }
+
+    #[test]
+    fn scores_outside_the_table_can_not_be_bought() {
+        assert_eq!(point_buy_cost(8), Some(0));
+        assert_eq!(point_buy_cost(15), Some(9));
+        assert_eq!(point_buy_cost(7), None);
+        assert_eq!(point_buy_cost(16), None);
+    }
+
+    #[test]
+    fn standard_array_spends_every_point() {
+        let spent: u32 = STANDARD_ARRAY
+            .iter()
+            .map(|&score| point_buy_cost(score).unwrap())
+            .sum();
+        assert_eq!(spent, POINT_BUY_POINTS);
+        let base = stats(STANDARD_ARRAY);
+        let bonus = stats([2, 0, 0, 1, 0, 0]);
+        assert_eq!(validate_abilities(&base, &bonus, true), Ok(()));
+    }
+
+    #[test]
+    fn spending_past_the_points_is_rejected() {
+        let base = stats([15, 15, 15, 8, 8, 8]);
+        let bonus = Stats::default();
+        assert_eq!(validate_abilities(&base, &bonus, true), Ok(()));
+        let base = stats([15, 15, 15, 9, 8, 8]);
+        assert_eq!(
+            validate_abilities(&base, &bonus, true),
+            Err("28 points spent, only 27 are available".to_string())
+        );
+    }
+
+    #[test]
+    fn bought_scores_stay_between_8_and_15() {
+        let bonus = Stats::default();
+        let base = stats([16, 8, 8, 8, 8, 8]);
+        assert_eq!(
+            validate_abilities(&base, &bonus, true),
+            Err("Strength must be bought between 8 and 15".to_string())
+        );
+        let base = stats([7, 8, 8, 8, 8, 8]);
+        assert!(validate_abilities(&base, &bonus, true).is_err());
+        //Rolled scores are not held to the point buy table
+        let base = stats([18, 3, 8, 8, 8, 8]);
+        assert_eq!(validate_abilities(&base, &bonus, false), Ok(()));
+    }
+
+    #[test]
+    fn race_bonus_is_added_before_the_cap() {
+        let base = stats([18, 8, 8, 8, 8, 8]);
+        let abilities = add_abilities(&base, &stats([2, 0, 1, 0, 0, 0]));
+        assert_eq!(abilities.strength, 20);
+        assert_eq!(abilities.constitution, 9);
+        assert_eq!(
+            validate_abilities(&base, &stats([2, 0, 0, 0, 0, 0]), false),
+            Ok(())
+        );
+        assert_eq!(
+            validate_abilities(&base, &stats([3, 0, 0, 0, 0, 0]), false),
+            Err("Strength can not be over 20".to_string())
+        );
+    }
+}
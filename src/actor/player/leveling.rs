@@ -0,0 +1,231 @@
+use crate::actor::player::classes::Classes;
+use crate::actor::player::races::Races;
+use crate::actor::player::{Character, MAX_LEVEL};
+use crate::actor::skills::proficiency_bonus;
+use crate::actor::{ability_modifier, ABILITIES};
+use crate::screen::Screen;
+use crate::utils::*;
+use convert_case::{Case, Casing};
+use sm::sm;
+
+sm! {
+    LevelUpState {
+        InitialStates { Idle }
+
+        ChooseHitPoints {
+            Idle, HitPoints, Abilities, Summary => HitPoints
+        }
+
+        ChooseAbilities {
+            HitPoints, Abilities, Summary => Abilities
+        }
+
+        ChooseSummary {
+            HitPoints, Abilities => Summary
+        }
+
+        Done {
+            Summary => Finished
+        }
+    }
+}
+use crate::actor::player::leveling::LevelUpState::{Variant::*, *};
+
+///Levels that give an ability score improvement
+const ABILITY_SCORE_IMPROVEMENT_LEVELS: [u32; 5] = [4, 8, 12, 16, 19];
+
+///Highest an ability score improvement can raise a score to
+const MAX_ABILITY_SCORE: u32 = 20;
+
+///Raise the character a level, letting the player choose how hit points are
+///gained and which abilities to improve
+pub fn level_up(
+    screen: &mut Screen,
+    character: &mut Character,
+    races: &Races,
+    classes: &Classes,
+) {
+    let original_level = character.level;
+    let level = character.level() + 1;
+    if level > MAX_LEVEL {
+        return;
+    }
+
+    let hit_die = classes.hit_die(&character.class) as u32;
+    let original_abilities = character.abilities.clone();
+    let original_hit_points = character.max_hit_points(races, classes);
+    let improvement = ABILITY_SCORE_IMPROVEMENT_LEVELS.contains(&level);
+    let mut hit_point_roll = 0;
+
+    let original_header = screen.get_header();
+    let mut sm = Machine::new(Idle).as_enum();
+    loop {
+        sm = match sm {
+            InitialIdle(m) => {
+                screen.set_header(&format!("Level Up - Level {}", level));
+
+                screen.set_msg(&format!(
+                    "{} has gained enough experience to reach level {}",
+                    character.name, level
+                ));
+                screen.draw_display();
+
+                m.transition(ChooseHitPoints).as_enum()
+            }
+            HitPointsByChooseHitPoints(m) => {
+                screen.set_header(&format!("Level Up - Level {} - Hit Points", level));
+
+                //Starting over undoes any improvement already chosen
+                character.abilities = original_abilities.clone();
+
+                let constitution = ability_modifier(character.abilities.constitution);
+                let average = hit_die / 2 + 1;
+                let choices = vec![
+                    format!("Take the average, {}", average),
+                    format!("Roll a d{}", hit_die),
+                ];
+                let chosen = screen.draw_menu("Choose how to gain hit points", &choices, &|_| {
+                    format!(
+                        "Each level adds the class hit die, d{}, plus the constitution \
+                         modifier of {:+}, always at least 1",
+                        hit_die, constitution
+                    )
+                });
+                match chosen {
+                    Some(number) => {
+                        hit_point_roll = if number == 0 {
+                            average
+                        } else {
+                            let roll = d(hit_die);
+                            screen.set_msg(&format!("You rolled {} on the d{}", roll, hit_die));
+                            screen.draw_display();
+                            roll
+                        };
+                        if improvement {
+                            m.transition(ChooseAbilities).as_enum()
+                        } else {
+                            m.transition(ChooseSummary).as_enum()
+                        }
+                    }
+                    //Hit points have to be chosen, there is nothing to go back to
+                    None => m.transition(ChooseHitPoints).as_enum(),
+                }
+            }
+            AbilitiesByChooseAbilities(m) => {
+                screen.set_header(&format!("Level Up - Level {} - Abilities", level));
+
+                character.abilities = original_abilities.clone();
+
+                let modes = vec![
+                    "Raise one ability by 2".to_string(),
+                    "Raise two abilities by 1".to_string(),
+                ];
+                match screen.draw_menu("Choose an ability score improvement", &modes, &|_| {
+                    format!("Scores can not be raised above {}", MAX_ABILITY_SCORE)
+                }) {
+                    Some(mode) => {
+                        let raises: Vec<u32> = if mode == 0 { vec![2] } else { vec![1, 1] };
+                        let mut raised: Vec<&str> = Vec::new();
+                        for raise in raises.iter() {
+                            let abilities: Vec<&str> = ABILITIES
+                                .iter()
+                                .filter(|ability| {
+                                    !raised.contains(ability)
+                                        && character.abilities.ability(ability) + raise
+                                            <= MAX_ABILITY_SCORE
+                                })
+                                .copied()
+                                .collect();
+                            let names: Vec<String> = abilities
+                                .iter()
+                                .map(|ability| {
+                                    let score = character.abilities.ability(ability);
+                                    format!(
+                                        "{:<13}{:>2} to {:>2}",
+                                        ability.to_case(Case::Title),
+                                        score,
+                                        score + raise
+                                    )
+                                })
+                                .collect();
+                            match screen.draw_menu(
+                                &format!("Choose ability to raise by {}", raise),
+                                &names,
+                                &|_| "".to_string(),
+                            ) {
+                                Some(number) => {
+                                    *character.abilities.ability_mut(abilities[number]) += raise;
+                                    raised.push(abilities[number]);
+                                }
+                                None => break,
+                            }
+                        }
+                        if raised.len() == raises.len() {
+                            m.transition(ChooseSummary).as_enum()
+                        } else {
+                            m.transition(ChooseAbilities).as_enum()
+                        }
+                    }
+                    None => m.transition(ChooseHitPoints).as_enum(),
+                }
+            }
+            SummaryByChooseSummary(m) => {
+                screen.set_header(&format!("Level Up - Level {} - Summary", level));
+
+                character.level = Some(level);
+                character.hit_point_rolls.push(hit_point_roll);
+
+                let mut msg: String = "".to_string();
+
+                msg = format!("{}Level: {} to {}\n\n", msg, level - 1, level);
+
+                msg = format!(
+                    "{}Hit Points: {} to {}\n\n",
+                    msg,
+                    original_hit_points,
+                    character.max_hit_points(races, classes)
+                );
+
+                msg = format!(
+                    "{}Proficiency Bonus: {:+} to {:+}\n\n",
+                    msg,
+                    proficiency_bonus(level - 1),
+                    proficiency_bonus(level)
+                );
+
+                for ability in ABILITIES.iter() {
+                    let before = original_abilities.ability(ability);
+                    let after = character.abilities.ability(ability);
+                    if before != after {
+                        msg = format!(
+                            "{}{}: {} to {}\n\n",
+                            msg,
+                            ability.to_case(Case::Title),
+                            before,
+                            after
+                        );
+                    }
+                }
+
+                let choices = vec!["Accept".to_string(), "Start over".to_string()];
+                let chosen = screen.draw_menu("Level up summary", &choices, &|_| msg.clone());
+                if chosen == Some(0) {
+                    m.transition(Done).as_enum()
+                } else {
+                    //Undo the level until the choices are made again
+                    character.level = original_level;
+                    character.hit_point_rolls.pop();
+                    if chosen.is_none() && improvement {
+                        m.transition(ChooseAbilities).as_enum()
+                    } else {
+                        m.transition(ChooseHitPoints).as_enum()
+                    }
+                }
+            }
+            FinishedByDone(_) => {
+                screen.set_header(&original_header);
+                break;
+            }
+        }
+    }
+}
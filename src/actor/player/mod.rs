@@ -1,26 +1,105 @@
+pub mod backgrounds;
 pub mod classes;
 mod generation;
+mod leveling;
 pub mod races;
+mod spellcasting;
 
-use crate::actor::player::classes::Classes;
+use crate::actor::conditions::{Condition, Conditions};
+use crate::actor::player::backgrounds::Backgrounds;
+use crate::actor::player::classes::{
+    CasterProgression, ClassFeature, Classes, Recharge, SpellPreparation, Spellcasting,
+};
 use crate::actor::player::generation::*;
-use crate::actor::player::races::Races;
-use crate::actor::{Alignment, Gender, Stats};
+pub use crate::actor::player::leveling::level_up;
+use crate::actor::player::races::{Races, SizeClass};
+pub use crate::actor::player::spellcasting::choose_spells;
+use crate::actor::skills::{proficiency_bonus, skill_ability, Check, SKILLS};
+use crate::actor::spells::Spells;
+use crate::actor::{ability_key, ability_modifier, Alignment, Gender, Stats, ABILITIES};
+use crate::items::ammunition::Ammunition;
 use crate::items::weapons::{Weapon, Weapons};
-use crate::screen::Screen;
+use crate::items::Item;
+use crate::screen::{Screen, COLUMN_WIDTH};
+use convert_case::{Case, Casing};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+///Highest level a character can reach
+pub const MAX_LEVEL: u32 = 20;
+
+///Experience needed to reach each level, starting at level 1
+const EXPERIENCE_THRESHOLDS: [u32; MAX_LEVEL as usize] = [
+    0, 300, 900, 2700, 6500, 14000, 23000, 34000, 48000, 64000, 85000, 100000, 120000, 140000,
+    165000, 195000, 225000, 265000, 305000, 355000,
+];
+
+///Pounds that can be carried per point of strength before slowing down
+const ENCUMBERED_PER_STRENGTH: f32 = 5.0;
+
+///Pounds per point of strength that slow a character down even more
+const HEAVILY_ENCUMBERED_PER_STRENGTH: f32 = 10.0;
+
+///How much the weight carried slows a character
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Encumbrance {
+    Unencumbered,
+    Encumbered,
+    Heavily,
+}
+
+impl Encumbrance {
+    ///Feet taken off the character's speed
+    pub fn speed_penalty(&self) -> u32 {
+        match self {
+            Encumbrance::Unencumbered => 0,
+            Encumbrance::Encumbered => 10,
+            Encumbrance::Heavily => 20,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Encumbrance::Unencumbered => "unencumbered",
+            Encumbrance::Encumbered => "encumbered",
+            Encumbrance::Heavily => "heavily encumbered",
+        }
+    }
+}
 
 #[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
 pub struct Character {
     pub name: String,
     pub gender: Gender,
     pub race: String,
     pub age: u32,
     pub class: String,
+    pub background: String,
     pub alignment: Alignment,
     pub abilities: Stats,
+    pub skill_proficiencies: Vec<String>,
+    pub tool_proficiencies: Vec<String>,
     pub weapons: Vec<Weapon>,
-    action_points: Option<u32>,
+    //The first weapon is held in the main hand, and the second in the off
+    //hand when this is set
+    off_hand: bool,
+    pub ammunition: Vec<Ammunition>,
+    level: Option<u32>,
+    experience: u32,
+    //Hit die rolled for each level after the first
+    hit_point_rolls: Vec<u32>,
+    //Hit points lost
+    damage: u32,
+    //Uses of each feature spent since it last recharged
+    feature_uses: HashMap<String, u32>,
+    //Features in effect, and the game time they end
+    active_features: HashMap<String, u32>,
+    //Spells known or prepared, cantrips included
+    pub spells: Vec<String>,
+    //Slots of each spell level spent since they last recharged
+    spell_slots_used: Vec<u32>,
+    pub conditions: Conditions,
 }
 
 impl Character {
@@ -29,27 +108,111 @@ impl Character {
         screen: &mut Screen,
         races: &Races,
         classes: &Classes,
+        backgrounds: &Backgrounds,
         weapons: &Weapons,
     ) -> Character {
-        generate(screen, races, classes, weapons)
+        generate(screen, races, classes, backgrounds, weapons)
     }
 
-    pub fn action_points(&self) -> u32 {
-        self.action_points.unwrap_or(0)
+    pub fn level(&self) -> u32 {
+        self.level.unwrap_or(1)
     }
 
-    pub fn action_points_increment(&mut self, points: u32) {
-        match self.action_points.as_mut() {
-            Some(x) => *x += points,
-            None => self.action_points = Some(points),
-        }
+    pub fn experience(&self) -> u32 {
+        self.experience
+    }
+
+    ///Experience needed for the next level, None at the highest level
+    pub fn next_level_experience(&self) -> Option<u32> {
+        EXPERIENCE_THRESHOLDS.get(self.level() as usize).copied()
     }
 
-    pub fn action_points_decrement(&mut self, points: u32) {
-        match self.action_points.as_mut() {
-            Some(x) => *x -= points,
-            None => self.action_points = Some(0),
+    pub fn gain_experience(
+        &mut self,
+        experience: u32,
+    ) {
+        self.experience += experience;
+    }
+
+    ///Enough experience has been gained for the next level
+    pub fn can_level_up(&self) -> bool {
+        matches!(self.next_level_experience(), Some(next) if self.experience >= next)
+    }
+
+    pub fn proficiency_bonus(&self) -> i32 {
+        proficiency_bonus(self.level())
+    }
+
+    pub fn is_skill_proficient(
+        &self,
+        skill: &str,
+    ) -> bool {
+        self.skill_proficiencies
+            .iter()
+            .any(|proficiency| proficiency == skill)
+    }
+
+    ///Ability modifier, plus the proficiency bonus when proficient in the skill,
+    ///None if there is no such skill
+    pub fn skill_modifier(
+        &self,
+        skill: &str,
+    ) -> Option<i32> {
+        let ability = skill_ability(skill)?;
+        Some(
+            ability_modifier(self.abilities.ability(ability))
+                + if self.is_skill_proficient(skill) {
+                    self.proficiency_bonus()
+                } else {
+                    0
+                },
+        )
+    }
+
+    ///Ability modifier, plus the proficiency bonus when the class is proficient
+    ///in saving throws for the ability
+    pub fn saving_throw_modifier(
+        &self,
+        classes: &Classes,
+        ability: &str,
+    ) -> i32 {
+        ability_modifier(self.abilities.ability(ability))
+            + if classes
+                .saving_throw_proficiencies(&self.class)
+                .iter()
+                .any(|proficiency| ability_key(proficiency) == ability)
+            {
+                self.proficiency_bonus()
+            } else {
+                0
+            }
+    }
+
+    ///Roll a saving throw to avoid a condition, with advantage if the race
+    ///is hardy against it and anything already hindering the character
+    ///
+    ///Returns true if the condition is avoided
+    pub fn resist_condition(
+        &self,
+        races: &Races,
+        classes: &Classes,
+        condition: Condition,
+        ability: &str,
+        difficulty: u32,
+    ) -> bool {
+        let ability = ability_key(ability);
+        if self.conditions.fails_save(ability) {
+            return false;
         }
+        Check::roll_with(
+            self.saving_throw_modifier(classes, ability),
+            difficulty,
+            races
+                .effects(&self.race)
+                .has_advantage(condition.advantage_key()),
+            self.conditions.save_disadvantage(ability),
+        )
+        .success()
     }
 
     pub fn is_weapon_proficient(
@@ -60,36 +223,12 @@ impl Character {
         weapon_key: &str,
     ) -> bool {
         //Run through race modifiers to check for weapon proficiencies
-        for modifier in races.modifiers(&self.race).iter() {
-            if modifier.modifier == "dwarven combat training" {
-                //You have proficiency with the battleaxe, handaxe, throwing hammer, and warhammer
-                if weapons.value(weapon_key).unwrap().name() == "battleaxe"
-                    || weapons.value(weapon_key).unwrap().name() == "handaxe"
-                    || weapons.value(weapon_key).unwrap().name() == "throwing hammer"
-                    || weapons.value(weapon_key).unwrap().name() == "warhammer"
-                {
-                    return true;
-                }
-            }
-            if modifier.modifier == "elf weapon training" {
-                //You have proficiency with the longsword, shortsword, shortbow, and longbow.
-                if weapons.value(weapon_key).unwrap().name() == "longsword"
-                    || weapons.value(weapon_key).unwrap().name() == "shortsword"
-                    || weapons.value(weapon_key).unwrap().name() == "shortbow"
-                    || weapons.value(weapon_key).unwrap().name() == "longbow"
-                {
-                    return true;
-                }
-            }
-            if modifier.modifier == "drow weapon training" {
-                //You have proficiency with rapiers, shortswords, and hand crossbows.
-                if weapons.value(weapon_key).unwrap().name() == "rapier"
-                    || weapons.value(weapon_key).unwrap().name() == "shortsword"
-                    || weapons.value(weapon_key).unwrap().name() == "hand crossbow"
-                {
-                    return true;
-                }
-            }
+        if races
+            .effects(&self.race)
+            .weapon_proficiencies
+            .contains(&weapons.value(weapon_key).unwrap().name())
+        {
+            return true;
         }
         //Run through class to check for weapon proficiencies
         for weapon_proficiency in classes.weapon_proficiencies(&self.class).iter() {
@@ -102,4 +241,893 @@ impl Character {
 
         false
     }
+
+    ///The class hit die plus the constitution modifier at first level, then the
+    ///hit die rolled plus the constitution modifier for each level after, plus
+    ///anything the race adds per level
+    pub fn max_hit_points(
+        &self,
+        races: &Races,
+        classes: &Classes,
+    ) -> u32 {
+        let constitution = ability_modifier(self.abilities.constitution);
+        let mut hit_points = (classes.hit_die(&self.class) as i32 + constitution).max(1) as u32;
+        for roll in self.hit_point_rolls.iter() {
+            hit_points += (*roll as i32 + constitution).max(1) as u32;
+        }
+        hit_points + races.effects(&self.race).hit_points_per_level * self.level()
+    }
+
+    ///Walking speed in feet, the race speed unless a modifier raises it
+    pub fn speed(
+        &self,
+        races: &Races,
+    ) -> u32 {
+        races
+            .effects(&self.race)
+            .speed
+            .unwrap_or_else(|| races.speed(&self.race).speed)
+    }
+
+    ///Speed turns come round at, after what is carried slows the character
+    pub fn current_speed(
+        &self,
+        races: &Races,
+    ) -> u32 {
+        self.speed(races)
+            .saturating_sub(self.encumbrance().speed_penalty())
+    }
+
+    ///Feet the character can move, none while held in place
+    pub fn movement_speed(
+        &self,
+        races: &Races,
+    ) -> u32 {
+        self.conditions.movement(self.current_speed(races))
+    }
+
+    ///Pounds of weapons and ammunition carried
+    pub fn carried_weight(&self) -> f32 {
+        self.weapons
+            .iter()
+            .map(|weapon| weapon.weight())
+            .chain(self.ammunition.iter().map(|ammunition| ammunition.weight()))
+            .sum()
+    }
+
+    ///How weighed down the character is, by carried weight against strength
+    pub fn encumbrance(&self) -> Encumbrance {
+        let strength = self.abilities.strength as f32;
+        let weight = self.carried_weight();
+        if weight > strength * HEAVILY_ENCUMBERED_PER_STRENGTH {
+            Encumbrance::Heavily
+        } else if weight > strength * ENCUMBERED_PER_STRENGTH {
+            Encumbrance::Encumbered
+        } else {
+            Encumbrance::Unencumbered
+        }
+    }
+
+    ///Hit points left
+    pub fn hit_points(
+        &self,
+        races: &Races,
+        classes: &Classes,
+    ) -> u32 {
+        self.max_hit_points(races, classes)
+            .saturating_sub(self.damage)
+    }
+
+    pub fn is_dead(
+        &self,
+        races: &Races,
+        classes: &Classes,
+    ) -> bool {
+        self.hit_points(races, classes) == 0
+    }
+
+    pub fn take_damage(
+        &mut self,
+        damage: u32,
+    ) {
+        self.damage += damage;
+    }
+
+    ///Regain hit points, up to the maximum
+    pub fn heal(
+        &mut self,
+        hit_points: u32,
+    ) {
+        self.damage = self.damage.saturating_sub(hit_points);
+    }
+
+    ///Class features reached at the current level
+    pub fn features(
+        &self,
+        classes: &Classes,
+    ) -> Vec<ClassFeature> {
+        classes
+            .features(&self.class)
+            .into_iter()
+            .filter(|feature| feature.level <= self.level())
+            .collect()
+    }
+
+    ///A class feature, if the current level has reached it
+    pub fn feature(
+        &self,
+        classes: &Classes,
+        name: &str,
+    ) -> Option<ClassFeature> {
+        self.features(classes)
+            .into_iter()
+            .find(|feature| feature.feature == name)
+    }
+
+    ///Uses of a feature left before it recharges, None if it can be used at will
+    pub fn feature_uses_left(
+        &self,
+        feature: &ClassFeature,
+    ) -> Option<u32> {
+        let spent = self
+            .feature_uses
+            .get(&feature.feature)
+            .copied()
+            .unwrap_or(0);
+        feature
+            .uses(self.level())
+            .map(|uses| uses.saturating_sub(spent))
+    }
+
+    ///Spend a use of a feature, starting it if it lasts a while
+    ///
+    ///Returns false if there are no uses left
+    pub fn use_feature(
+        &mut self,
+        feature: &ClassFeature,
+        time: u32,
+    ) -> bool {
+        if self.feature_uses_left(feature) == Some(0) {
+            return false;
+        }
+        *self
+            .feature_uses
+            .entry(feature.feature.clone())
+            .or_insert(0) += 1;
+        if feature.duration > 0 {
+            self.active_features
+                .insert(feature.feature.clone(), time + feature.duration);
+        }
+        true
+    }
+
+    ///Check if a feature that lasts a while is in effect
+    pub fn is_feature_active(
+        &self,
+        name: &str,
+        time: u32,
+    ) -> bool {
+        matches!(self.active_features.get(name), Some(&end) if time < end)
+    }
+
+    ///Recover from a rest, a long rest restores all hit points and recharges
+    ///every feature, a short rest only recharges features that allow it
+    pub fn rest(
+        &mut self,
+        classes: &Classes,
+        long: bool,
+    ) {
+        for feature in classes.features(&self.class).iter() {
+            if long || feature.recharge == Some(Recharge::ShortRest) {
+                self.feature_uses.remove(&feature.feature);
+            }
+        }
+        self.active_features.clear();
+        if let Some(spellcasting) = self.spellcasting(classes) {
+            if long || spellcasting.recharge() == Recharge::ShortRest {
+                self.spell_slots_used.clear();
+            }
+        }
+        if long {
+            self.damage = 0;
+        }
+    }
+
+    pub fn spellcasting(
+        &self,
+        classes: &Classes,
+    ) -> Option<Spellcasting> {
+        classes.spellcasting(&self.class)
+    }
+
+    ///Modifier of the ability spells are cast with
+    fn spellcasting_modifier(
+        &self,
+        spellcasting: &Spellcasting,
+    ) -> i32 {
+        ability_modifier(self.abilities.ability(ability_key(&spellcasting.ability)))
+    }
+
+    ///Bonus to hit with spell attacks
+    pub fn spell_attack_modifier(
+        &self,
+        classes: &Classes,
+    ) -> i32 {
+        self.spellcasting(classes).map_or(0, |spellcasting| {
+            self.proficiency_bonus() + self.spellcasting_modifier(&spellcasting)
+        })
+    }
+
+    ///Difficulty class of saving throws against the character's spells
+    pub fn spell_save_dc(
+        &self,
+        classes: &Classes,
+    ) -> i32 {
+        8 + self.spell_attack_modifier(classes)
+    }
+
+    ///Cantrips and other spells the character can know or have prepared
+    pub fn spells_allowed(
+        &self,
+        classes: &Classes,
+    ) -> (u32, u32) {
+        let spellcasting = match self.spellcasting(classes) {
+            Some(spellcasting) => spellcasting,
+            None => return (0, 0),
+        };
+        let level = self.level();
+        let spells = if spellcasting.max_spell_level(level) == 0 {
+            0
+        } else {
+            match spellcasting.preparation {
+                SpellPreparation::Known => spellcasting.known(level),
+                SpellPreparation::Prepared => {
+                    let caster_level = if spellcasting.progression == CasterProgression::Half {
+                        level / 2
+                    } else {
+                        level
+                    };
+                    (self.spellcasting_modifier(&spellcasting) + caster_level as i32).max(1) as u32
+                }
+            }
+        };
+        (spellcasting.cantrips(level), spells)
+    }
+
+    ///Check if there is room for more cantrips or spells
+    pub fn can_choose_spells(
+        &self,
+        classes: &Classes,
+        spells: &Spells,
+    ) -> bool {
+        let (cantrips_allowed, spells_allowed) = self.spells_allowed(classes);
+        let cantrips = self
+            .spells
+            .iter()
+            .filter(|spell| spells.value(spell).is_some_and(|spell| spell.is_cantrip()))
+            .count() as u32;
+        cantrips < cantrips_allowed || self.spells.len() as u32 - cantrips < spells_allowed
+    }
+
+    ///Spell slots left of a spell level, none outside levels 1 to 9
+    pub fn spell_slots_left(
+        &self,
+        classes: &Classes,
+        spell_level: u32,
+    ) -> u32 {
+        if !(1..=9).contains(&spell_level) {
+            return 0;
+        }
+        let index = spell_level as usize - 1;
+        let slots = self
+            .spellcasting(classes)
+            .map_or(0, |spellcasting| spellcasting.slots(self.level())[index]);
+        slots.saturating_sub(self.spell_slots_used.get(index).copied().unwrap_or(0))
+    }
+
+    ///Spend the lowest slot that can cast a spell of a level
+    ///
+    ///Returns the level of the slot spent, None if there are none left
+    pub fn spend_spell_slot(
+        &mut self,
+        classes: &Classes,
+        spell_level: u32,
+    ) -> Option<u32> {
+        let slot_level =
+            (spell_level.max(1)..=9).find(|&level| self.spell_slots_left(classes, level) > 0)?;
+        let index = slot_level as usize - 1;
+        if self.spell_slots_used.len() <= index {
+            self.spell_slots_used.resize(index + 1, 0);
+        }
+        self.spell_slots_used[index] += 1;
+        Some(slot_level)
+    }
+
+    ///Slots left and total of each spell level the character has, for display
+    pub fn spell_slots_summary(
+        &self,
+        classes: &Classes,
+    ) -> Vec<String> {
+        let spellcasting = match self.spellcasting(classes) {
+            Some(spellcasting) => spellcasting,
+            None => return Vec::new(),
+        };
+        spellcasting
+            .slots(self.level())
+            .iter()
+            .enumerate()
+            .filter(|(_, &count)| count > 0)
+            .map(|(index, &count)| {
+                format!(
+                    "{}/{}",
+                    self.spell_slots_left(classes, index as u32 + 1),
+                    count
+                )
+            })
+            .collect()
+    }
+
+    ///Weapon held in the main hand
+    pub fn main_hand(&self) -> Option<&Weapon> {
+        self.weapons.first()
+    }
+
+    ///Weapon held in the off hand, nothing while the main hand needs both
+    pub fn off_hand(&self) -> Option<&Weapon> {
+        if self.off_hand && !self.main_hand()?.two_handed() {
+            self.weapons.get(1)
+        } else {
+            None
+        }
+    }
+
+    ///Hold the weapons at the given indexes, the off hand left empty with None
+    pub fn wield(
+        &mut self,
+        main_hand: usize,
+        off_hand: Option<usize>,
+    ) {
+        let main_weapon = self.weapons[main_hand].clone();
+        let off_weapon = off_hand.map(|index| self.weapons[index].clone());
+        let mut indexes = vec![main_hand];
+        indexes.extend(off_hand);
+        indexes.sort_unstable_by(|a, b| b.cmp(a));
+        for index in indexes {
+            self.weapons.remove(index);
+        }
+        self.off_hand = off_weapon.is_some();
+        if let Some(off_weapon) = off_weapon {
+            self.weapons.insert(0, off_weapon);
+        }
+        self.weapons.insert(0, main_weapon);
+    }
+
+    ///Take a carried weapon out of the hands or pack, such as to throw it
+    ///
+    ///One in the pack is taken first, leaving the weapons in hand be
+    pub fn take_weapon(
+        &mut self,
+        weapon: &str,
+    ) -> Option<Weapon> {
+        let in_hand = if self.off_hand { 2 } else { 1 };
+        let matching = |index: &usize| self.weapons[*index].name() == weapon;
+        let index = (in_hand..self.weapons.len())
+            .find(matching)
+            .or_else(|| (0..in_hand.min(self.weapons.len())).find(matching))?;
+        //Whatever is left in the off hand moves over to the main hand
+        if index <= 1 {
+            self.off_hand = false;
+        }
+        Some(self.weapons.remove(index))
+    }
+
+    ///Small creatures have a hard time wielding heavy weapons
+    pub fn is_small(
+        &self,
+        races: &Races,
+    ) -> bool {
+        races.size(&self.race).class() == SizeClass::Small
+    }
+
+    ///Pieces of ammunition carried that a weapon can fire
+    pub fn ammunition_left(
+        &self,
+        weapon: &str,
+    ) -> u32 {
+        self.ammunition
+            .iter()
+            .filter(|ammunition| ammunition.fits(weapon))
+            .map(|ammunition| ammunition.quantity())
+            .sum()
+    }
+
+    ///Take one piece of ammunition for a weapon out of the pack
+    pub fn take_ammunition(
+        &mut self,
+        weapon: &str,
+    ) -> Option<Ammunition> {
+        let ammunition = self
+            .ammunition
+            .iter_mut()
+            .find(|ammunition| ammunition.fits(weapon) && ammunition.quantity() > 0)?;
+        Some(ammunition.split(1))
+    }
+
+    ///Put an item in the pack
+    pub fn pick_up(
+        &mut self,
+        item: Item,
+    ) {
+        match item {
+            Item::Weapon(weapon) => self.weapons.push(weapon),
+            Item::Ammunition(ammunition) => {
+                match self
+                    .ammunition
+                    .iter_mut()
+                    .find(|carried| carried.name() == ammunition.name())
+                {
+                    Some(carried) => carried.merge(&ammunition),
+                    None => self.ammunition.push(ammunition),
+                }
+            }
+        }
+    }
+
+    ///Armor class without armor, 10 plus the dexterity modifier
+    pub fn armor_class(&self) -> i32 {
+        10 + ability_modifier(self.abilities.dexterity)
+    }
+
+    fn detail_name(&self) -> String {
+        format!("{}\n", self.name)
+    }
+
+    fn detail_summary(
+        &self,
+        races: &Races,
+        classes: &Classes,
+    ) -> String {
+        format!(
+            "- {} {} {}, {}, age {}\n- Level {}{}\n- Experience: {}\n",
+            if self.gender == Gender::M {
+                "Male"
+            } else {
+                "Female"
+            },
+            races.detail_race(&self.race).trim(),
+            classes.detail_class(&self.class).trim(),
+            self.alignment.name(),
+            self.age,
+            self.level(),
+            if self.background.is_empty() {
+                "".to_string()
+            } else {
+                format!(", {} background", self.background.to_case(Case::Title))
+            },
+            match self.next_level_experience() {
+                Some(next) => format!("{} of {} for the next level", self.experience, next),
+                None => self.experience.to_string(),
+            }
+        )
+    }
+
+    fn detail_abilities(
+        &self,
+        classes: &Classes,
+    ) -> String {
+        let mut abilities_str: String;
+
+        abilities_str = "- Abilities\n".to_string();
+
+        for ability in ABILITIES.iter() {
+            let score = self.abilities.ability(ability);
+            abilities_str = format!(
+                "{}  - {:<13}{:>2} ({:+}), save {:+}\n",
+                abilities_str,
+                ability.to_case(Case::Title),
+                score,
+                ability_modifier(score),
+                self.saving_throw_modifier(classes, ability)
+            );
+        }
+
+        abilities_str
+    }
+
+    fn detail_defenses(
+        &self,
+        races: &Races,
+        classes: &Classes,
+    ) -> String {
+        let effects = races.effects(&self.race);
+        let mut defenses_str = format!(
+            "- Hit Points: {}/{}\n- Armor Class: {}\n- Proficiency Bonus: {:+}\n- Speed: {} ft\n",
+            self.hit_points(races, classes),
+            self.max_hit_points(races, classes),
+            self.armor_class(),
+            self.proficiency_bonus(),
+            self.speed(races)
+        );
+
+        let encumbrance = self.encumbrance();
+        defenses_str = format!(
+            "{}- Carrying: {} lb{}\n",
+            defenses_str,
+            self.carried_weight(),
+            if encumbrance == Encumbrance::Unencumbered {
+                "".to_string()
+            } else {
+                format!(
+                    ", {} (-{} ft)",
+                    encumbrance.name(),
+                    encumbrance.speed_penalty()
+                )
+            }
+        );
+
+        if effects.darkvision > 0 {
+            defenses_str = format!("{}- Darkvision: {} ft\n", defenses_str, effects.darkvision);
+        }
+
+        for condition in self.conditions.iter() {
+            defenses_str = format!(
+                "{}- {}: {}\n",
+                defenses_str,
+                condition.name().to_case(Case::Title),
+                condition.description()
+            );
+        }
+
+        for (heading, list) in [
+            ("Resistances", &effects.resistances),
+            ("Advantage Against", &effects.advantages),
+        ]
+        .iter()
+        {
+            if !list.is_empty() {
+                defenses_str = format!(
+                    "{}- {}: {}\n",
+                    defenses_str,
+                    heading,
+                    list.join(", ").to_case(Case::Title)
+                );
+            }
+        }
+
+        defenses_str
+    }
+
+    fn detail_skills(&self) -> String {
+        let mut skills_str: String;
+
+        skills_str = "- Skills, * is proficient\n".to_string();
+
+        for (skill, ability) in SKILLS.iter() {
+            skills_str = format!(
+                "{}  {} {:<16}{:>3} ({})\n",
+                skills_str,
+                if self.is_skill_proficient(skill) {
+                    "*"
+                } else {
+                    "-"
+                },
+                skill.to_case(Case::Title),
+                format!("{:+}", self.skill_modifier(skill).unwrap_or(0)),
+                &ability[..3].to_case(Case::Title)
+            );
+        }
+
+        skills_str
+    }
+
+    ///Indented list under a heading
+    fn detail_list(
+        heading: &str,
+        list: &[String],
+    ) -> String {
+        let mut list_str: String;
+
+        list_str = format!("- {}\n", heading);
+
+        if !list.is_empty() {
+            list_str = format!(
+                "{}{}\n",
+                list_str,
+                textwrap::fill(
+                    &list.join(", ").to_case(Case::Title),
+                    textwrap::Options::new(COLUMN_WIDTH)
+                        .initial_indent("  - ")
+                        .subsequent_indent("    ")
+                )
+            );
+        }
+
+        list_str
+    }
+
+    fn detail_proficiencies(
+        &self,
+        races: &Races,
+        classes: &Classes,
+    ) -> String {
+        let effects = races.effects(&self.race);
+        let mut armor_proficiencies = classes.armor_proficiencies(&self.class);
+        armor_proficiencies.extend(effects.armor_proficiencies);
+        let mut weapon_proficiencies = classes.weapon_proficiencies(&self.class);
+        weapon_proficiencies.extend(effects.weapon_proficiencies);
+
+        [
+            Character::detail_list(
+                "Saving Throw Proficiencies",
+                &classes.saving_throw_proficiencies(&self.class),
+            ),
+            Character::detail_list("Armor Proficiencies", &armor_proficiencies),
+            Character::detail_list("Weapon Proficiencies", &weapon_proficiencies),
+            Character::detail_list("Tool Proficiencies", &self.tool_proficiencies),
+        ]
+        .join("")
+    }
+
+    fn detail_modifiers(
+        &self,
+        races: &Races,
+    ) -> String {
+        let modifiers: Vec<String> = races
+            .modifiers(&self.race)
+            .iter()
+            .map(|modifier| modifier.modifier.clone())
+            .collect();
+        Character::detail_list("Racial Traits", &modifiers)
+    }
+
+    fn detail_languages(
+        &self,
+        races: &Races,
+    ) -> String {
+        Character::detail_list("Languages", &races.languages(&self.race))
+    }
+
+    fn detail_weapons(&self) -> String {
+        let mut weapons_str: String;
+
+        weapons_str = "- Weapons\n".to_string();
+
+        for (index, weapon) in self.weapons.iter().enumerate() {
+            let hand = if index == 0 {
+                " (main hand)"
+            } else if index == 1 && self.off_hand().is_some() {
+                " (off hand)"
+            } else {
+                ""
+            };
+            for (number, line) in weapon.details().lines().enumerate() {
+                if number == 0 {
+                    weapons_str = format!("{}  {}{}\n", weapons_str, line, hand);
+                } else {
+                    weapons_str = format!("{}  {}\n", weapons_str, line);
+                }
+            }
+        }
+
+        weapons_str
+    }
+
+    fn detail_ammunition(&self) -> String {
+        let ammunition: Vec<String> = self
+            .ammunition
+            .iter()
+            .filter(|ammunition| ammunition.quantity() > 0)
+            .map(|ammunition| ammunition.detail_name().trim().to_string())
+            .collect();
+        Character::detail_list("Ammunition", &ammunition)
+    }
+
+    fn detail_spells(
+        &self,
+        classes: &Classes,
+        spells: &Spells,
+    ) -> String {
+        let spellcasting = match self.spellcasting(classes) {
+            Some(spellcasting) => spellcasting,
+            None => return "".to_string(),
+        };
+        let mut spells_str: String;
+
+        spells_str = format!(
+            "- Spells\n  - Cast with {}, attack {:+}, save DC {}\n",
+            spellcasting.ability.to_case(Case::Title),
+            self.spell_attack_modifier(classes),
+            self.spell_save_dc(classes)
+        );
+
+        let slots = self.spell_slots_summary(classes);
+        if !slots.is_empty() {
+            spells_str = format!("{}  - Slots by level: {}\n", spells_str, slots.join(" "));
+        }
+
+        for spell in self.spells.iter().filter_map(|spell| spells.value(spell)) {
+            spells_str = format!(
+                "{}  - {}\n{}\n",
+                spells_str,
+                spell.name().to_case(Case::Title),
+                textwrap::fill(
+                    &spell.summary(),
+                    textwrap::Options::new(COLUMN_WIDTH)
+                        .initial_indent("    ")
+                        .subsequent_indent("    ")
+                )
+            );
+        }
+
+        spells_str
+    }
+
+    ///Character sheet
+    pub fn details(
+        &self,
+        races: &Races,
+        classes: &Classes,
+        spells: &Spells,
+    ) -> String {
+        [
+            self.detail_name(),
+            self.detail_summary(races, classes),
+            self.detail_abilities(classes),
+            self.detail_defenses(races, classes),
+            self.detail_skills(),
+            self.detail_proficiencies(races, classes),
+            self.detail_modifiers(races),
+            self.detail_languages(races),
+            self.detail_weapons(),
+            self.detail_ammunition(),
+            self.detail_spells(classes, spells),
+        ]
+        .join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::energy::Action;
+
+    fn character(race: &str) -> Character {
+        Character {
+            race: race.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn restrained_acts_as_often_but_can_not_move() {
+        let races = Races::new();
+        let mut character = character("hill dwarf");
+        let speed = character.current_speed(&races);
+        character.conditions.add(Condition::Restrained, 60);
+        assert_eq!(character.current_speed(&races), speed);
+        assert_eq!(
+            Action::Attack.duration(character.current_speed(&races)),
+            Action::Attack.duration(speed)
+        );
+        assert_eq!(character.movement_speed(&races), 0);
+    }
+
+    ///A hill dwarf carrying weapons, wielding the first and, if set, the
+    ///second in the off hand
+    fn armed(
+        names: &[&str],
+        dual: bool,
+    ) -> Character {
+        let weapons = Weapons::new();
+        let mut armed = character("hill dwarf");
+        armed.weapons = names
+            .iter()
+            .map(|name| weapons.weapon(name).unwrap())
+            .collect();
+        armed.wield(0, dual.then_some(1));
+        armed
+    }
+
+    #[test]
+    fn throwing_takes_from_the_pack_before_the_hands() {
+        let mut thrower = armed(&["handaxe", "dagger", "handaxe"], true);
+        assert!(thrower.take_weapon("handaxe").is_some());
+        assert_eq!(thrower.main_hand().unwrap().name(), "handaxe");
+        assert_eq!(thrower.off_hand().unwrap().name(), "dagger");
+
+        //With the off hand free the second weapon is in the pack
+        let mut thrower = armed(&["longsword", "handaxe"], false);
+        assert!(thrower.take_weapon("handaxe").is_some());
+        assert_eq!(thrower.main_hand().unwrap().name(), "longsword");
+    }
+
+    #[test]
+    fn throwing_the_last_one_empties_the_hand() {
+        let mut thrower = armed(&["handaxe", "dagger"], true);
+        assert!(thrower.take_weapon("handaxe").is_some());
+        assert_eq!(thrower.main_hand().unwrap().name(), "dagger");
+        assert!(thrower.off_hand().is_none());
+        assert!(thrower.take_weapon("handaxe").is_none());
+    }
+
+    #[test]
+    fn skill_modifier_adds_proficiency() {
+        let mut character = character("high elf");
+        character.abilities.dexterity = 14;
+        assert_eq!(character.skill_modifier("stealth"), Some(2));
+        character.skill_proficiencies.push("stealth".to_string());
+        assert_eq!(character.skill_modifier("stealth"), Some(4));
+        assert_eq!(character.skill_modifier("juggling"), None);
+    }
+
+    #[test]
+    fn levels_up_at_each_threshold() {
+        let mut character = character("high elf");
+        character.experience = 299;
+        assert!(!character.can_level_up());
+        character.experience = 300;
+        assert!(character.can_level_up());
+        character.level = Some(2);
+        assert!(!character.can_level_up());
+        assert_eq!(character.next_level_experience(), Some(900));
+        character.experience = 900;
+        assert!(character.can_level_up());
+    }
+
+    #[test]
+    fn no_level_past_the_highest() {
+        let mut character = character("high elf");
+        character.experience = u32::MAX;
+        character.level = Some(MAX_LEVEL - 1);
+        assert_eq!(character.next_level_experience(), Some(355000));
+        assert!(character.can_level_up());
+        character.level = Some(MAX_LEVEL);
+        assert_eq!(character.next_level_experience(), None);
+        assert!(!character.can_level_up());
+    }
+
+    #[test]
+    fn every_level_gives_at_least_one_hit_point() {
+        let (races, classes) = (Races::new(), Classes::new());
+        let mut character = character("high elf");
+        character.class = "wizard".to_string();
+        character.abilities.constitution = 1;
+        character.level = Some(3);
+        character.hit_point_rolls = vec![1, 6];
+        assert_eq!(character.max_hit_points(&races, &classes), 3);
+
+        character.abilities.constitution = 14;
+        assert_eq!(
+            character.max_hit_points(&races, &classes),
+            (6 + 2) + (1 + 2) + (6 + 2)
+        );
+
+        //Hill dwarves gain another hit point every level
+        character.race = "hill dwarf".to_string();
+        assert_eq!(character.max_hit_points(&races, &classes), 19 + 3);
+    }
+
+    #[test]
+    fn no_slots_outside_spell_levels() {
+        let classes = Classes::new();
+        let mut character = character("high elf");
+        character.class = "wizard".to_string();
+        assert!(character.spell_slots_left(&classes, 1) > 0);
+        assert_eq!(character.spell_slots_left(&classes, 0), 0);
+        assert_eq!(character.spell_slots_left(&classes, 10), 0);
+        assert_eq!(character.spend_spell_slot(&classes, 10), None);
+    }
+
+    #[test]
+    fn spending_moves_up_to_the_next_slot_left() {
+        let classes = Classes::new();
+        let mut character = character("high elf");
+        character.class = "wizard".to_string();
+        let slots = character.spell_slots_left(&classes, 1);
+        for _ in 0..slots {
+            assert_eq!(character.spend_spell_slot(&classes, 1), Some(1));
+        }
+        assert_eq!(character.spell_slots_left(&classes, 1), 0);
+        assert_eq!(character.spend_spell_slot(&classes, 1), None);
+    }
 }
@@ -1,5 +1,6 @@
 use rand::Rng;
 
+use crate::actor::effects::{Effect, Effects};
 use crate::actor::{Stats, Alignment, Gender};
 use crate::screen::COLUMN_WIDTH;
 use convert_case::{Case, Casing};
@@ -14,17 +15,17 @@ pub struct RaceAbilities {
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
 pub struct RaceAge {
     description: String,
-    adulthood: u32,
-    lifespan: u32,
+    pub adulthood: u32,
+    pub lifespan: u32,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
 pub struct RaceAlignment {
     description: String,
-    alignment: Alignment,
+    pub alignment: Alignment,
 }
 
-#[derive(Clone, Serialize, Deserialize, Debug)]
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
 pub enum SizeClass {
     Small,
     Medium,
@@ -44,6 +45,12 @@ pub struct RaceSize {
     class: SizeClass,
 }
 
+impl RaceSize {
+    pub fn class(&self) -> SizeClass {
+        self.class
+    }
+}
+
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
 pub struct RaceNames {
     description: String,
@@ -61,13 +68,15 @@ pub struct RaceNames {
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
 pub struct RaceSpeed {
     description: String,
-    speed: u32,
+    pub speed: u32,
 }
 
 #[derive(Clone, Eq, Ord, PartialEq, PartialOrd, Serialize, Deserialize, Debug, Default)]
 pub struct RaceModifier {
     description: String,
     pub modifier: String,
+    #[serde(default)]
+    pub effects: Vec<Effect>,
 }
 
 #[derive(Clone, Serialize, Deserialize, Debug, Default)]
@@ -99,6 +108,12 @@ pub struct Races {
     races: Vec<Race>,
 }
 
+impl Default for Races {
+    fn default() -> Self {
+        Races::new()
+    }
+}
+
 //Each race mayhave subraces
 //If a race has a subrace, only consider those subraces as races
 impl Races {
@@ -229,6 +244,16 @@ impl Races {
         names
     }
 
+    ///Random age between adulthood and middle age
+    pub fn generate_age(
+        &self,
+        key: &str,
+    ) -> u32 {
+        let age = self.age(key);
+        let mut rng = rand::thread_rng();
+        rng.gen_range(age.adulthood..=(age.lifespan / 2).max(age.adulthood))
+    }
+
     pub fn generate_name(
         &self,
         key: &str,
@@ -393,6 +418,19 @@ impl Races {
         modifiers
     }
 
+    ///Everything granted by the race and subrace modifiers
+    pub fn effects(
+        &self,
+        key: &str,
+    ) -> Effects {
+        let modifiers = self.modifiers(key);
+        Effects::new(
+            modifiers
+                .iter()
+                .flat_map(|modifier| modifier.effects.iter()),
+        )
+    }
+
     pub fn languages(
         &self,
         key: &str,
@@ -617,7 +655,7 @@ impl Races {
         ability_score_increase_str
     }
 
-    fn detail_age(
+    pub fn detail_age(
         &self,
         key: &str,
     ) -> String {
@@ -645,7 +683,7 @@ impl Races {
         age_str
     }
 
-    fn detail_alignment(
+    pub fn detail_alignment(
         &self,
         key: &str,
     ) -> String {
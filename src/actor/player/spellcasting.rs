@@ -0,0 +1,104 @@
+use crate::actor::player::classes::Classes;
+use crate::actor::player::Character;
+use crate::actor::spells::Spells;
+use crate::screen::Screen;
+use convert_case::{Case, Casing};
+
+///Let the player pick cantrips and spells up to what the class allows
+///
+///Spells already chosen can only be swapped out when replacing, as when a
+///prepared caster finishes a long rest
+pub fn choose_spells(
+    screen: &mut Screen,
+    character: &mut Character,
+    classes: &Classes,
+    spells: &Spells,
+    replace: bool,
+) {
+    let spellcasting = match character.spellcasting(classes) {
+        Some(spellcasting) => spellcasting,
+        None => return,
+    };
+    let (cantrips_allowed, spells_allowed) = character.spells_allowed(classes);
+    let keys = spells.keys_for_class(
+        &character.class,
+        spellcasting.max_spell_level(character.level()),
+    );
+    let original: Vec<String> = character.spells.clone();
+
+    //Forget anything no longer on the class list
+    character.spells.retain(|spell| keys.contains(spell));
+
+    let original_header = screen.get_header();
+    let original_msg = screen.get_msg();
+    screen.set_header(&format!(
+        "Spells - {}",
+        character.class.to_case(Case::Title)
+    ));
+    loop {
+        let chosen_count = |cantrip: bool| {
+            character
+                .spells
+                .iter()
+                .filter_map(|spell| spells.value(spell))
+                .filter(|spell| spell.is_cantrip() == cantrip)
+                .count() as u32
+        };
+        let cantrips = chosen_count(true);
+        let leveled = chosen_count(false);
+
+        let mut names: Vec<String> = vec!["Done".to_string()];
+        names.extend(keys.iter().map(|key| {
+            format!(
+                "[{}] {}",
+                if character.spells.contains(key) {
+                    "x"
+                } else {
+                    " "
+                },
+                key.to_case(Case::Title)
+            )
+        }));
+        let msg = format!(
+            "Choose spells, {}/{} cantrips and {}/{} spells chosen",
+            cantrips, cantrips_allowed, leveled, spells_allowed
+        );
+        let chosen = screen.draw_menu(&msg, &names, &|index| {
+            if index == 0 {
+                "Finish choosing spells, anything left unchosen can be chosen later".to_string()
+            } else {
+                spells.value(&keys[index - 1]).unwrap().details()
+            }
+        });
+
+        let index = match chosen {
+            Some(0) | None => break,
+            Some(index) => index - 1,
+        };
+        let key = &keys[index];
+        let cantrip = spells.value(key).unwrap().is_cantrip();
+        if let Some(position) = character.spells.iter().position(|spell| spell == key) {
+            if replace || !original.contains(key) {
+                character.spells.remove(position);
+            } else {
+                screen.set_msg(&format!(
+                    "{} is already known, it can not be forgotten now",
+                    key.to_case(Case::Title)
+                ));
+                screen.draw_display();
+            }
+        } else if (cantrip && cantrips < cantrips_allowed) || (!cantrip && leveled < spells_allowed)
+        {
+            character.spells.push(key.clone());
+        } else {
+            screen.set_msg(&format!(
+                "No more {} can be chosen, remove one first",
+                if cantrip { "cantrips" } else { "spells" }
+            ));
+            screen.draw_display();
+        }
+    }
+
+    screen.set_header(&original_header);
+    screen.set_msg(&original_msg);
+}
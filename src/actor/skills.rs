@@ -0,0 +1,69 @@
+use crate::utils::d20;
+
+///The 18 skills and the ability each one is checked with
+pub const SKILLS: [(&str, &str); 18] = [
+    ("acrobatics", "dexterity"),
+    ("animal handling", "wisdom"),
+    ("arcana", "intellect"),
+    ("athletics", "strength"),
+    ("deception", "charisma"),
+    ("history", "intellect"),
+    ("insight", "wisdom"),
+    ("intimidation", "charisma"),
+    ("investigation", "intellect"),
+    ("medicine", "wisdom"),
+    ("nature", "intellect"),
+    ("perception", "wisdom"),
+    ("performance", "charisma"),
+    ("persuasion", "charisma"),
+    ("religion", "intellect"),
+    ("sleight of hand", "dexterity"),
+    ("stealth", "dexterity"),
+    ("survival", "wisdom"),
+];
+
+///Ability a skill is checked with, None if there is no such skill
+pub fn skill_ability(skill: &str) -> Option<&'static str> {
+    SKILLS
+        .iter()
+        .find(|(name, _)| *name == skill)
+        .map(|(_, ability)| *ability)
+}
+
+///Bonus added to anything a character is proficient in, rising every 4 levels
+pub fn proficiency_bonus(level: u32) -> i32 {
+    2 + (level.max(1) as i32 - 1) / 4
+}
+
+///A d20 roll plus a modifier against a difficulty class
+#[derive(Clone, Copy, Debug)]
+pub struct Check {
+    pub roll: u32,
+    pub modifier: i32,
+    pub difficulty: u32,
+}
+
+impl Check {
+    ///Roll a d20 for the check with advantage or disadvantage
+    pub fn roll_with(
+        modifier: i32,
+        difficulty: u32,
+        advantage: bool,
+        disadvantage: bool,
+    ) -> Check {
+        Check {
+            roll: d20(advantage, disadvantage),
+            modifier,
+            difficulty,
+        }
+    }
+
+    pub fn total(&self) -> i32 {
+        self.roll as i32 + self.modifier
+    }
+
+    ///Meeting the difficulty class succeeds
+    pub fn success(&self) -> bool {
+        self.total() >= self.difficulty as i32
+    }
+}
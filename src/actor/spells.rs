@@ -0,0 +1,390 @@
+use crate::actor::conditions::Inflicts;
+use crate::screen::COLUMN_WIDTH;
+use crate::utils::*;
+use convert_case::{Case, Casing};
+use serde::{Deserialize, Serialize};
+
+///Feet covered by one map tile
+pub const FEET_PER_TILE: u32 = 5;
+
+///Character levels where cantrips gain another damage die
+const CANTRIP_LEVELS: [u32; 3] = [5, 11, 17];
+
+///Shape of the area a spell covers
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SpellShape {
+    ///Radius around the target point
+    Sphere,
+    ///Square centered on the target point
+    Cube,
+    ///Widening out from the caster toward the target, as wide as it is long
+    Cone,
+    ///Straight out from the caster toward the target
+    Line,
+}
+
+#[derive(Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct SpellArea {
+    pub shape: SpellShape,
+    //Feet, the radius of a sphere and the length of anything else
+    pub size: u32,
+}
+
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Spell {
+    spell: String,
+    level: u32,
+    school: String,
+    description: String,
+    //Feet, 0 for spells centered on or touching the caster
+    range: u32,
+    #[serde(default)]
+    area: Option<SpellArea>,
+    //Makes a spell attack roll against armor class
+    #[serde(default)]
+    attack: bool,
+    //Ability targets save with to avoid the spell, half damage for areas
+    #[serde(default)]
+    saving_throw: Option<String>,
+    #[serde(default)]
+    damage: Option<String>,
+    #[serde(default)]
+    healing: Option<String>,
+    //Condition left on targets that fail the saving throw or are hit
+    #[serde(default)]
+    inflicts: Option<Inflicts>,
+    classes: Vec<String>,
+}
+
+impl Spell {
+    pub fn name(&self) -> String {
+        self.spell.clone()
+    }
+
+    ///Spell level, 0 for a cantrip
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    pub fn is_cantrip(&self) -> bool {
+        self.level == 0
+    }
+
+    pub fn range(&self) -> u32 {
+        self.range
+    }
+
+    ///Range in map tiles
+    pub fn range_tiles(&self) -> usize {
+        (self.range / FEET_PER_TILE) as usize
+    }
+
+    pub fn area(&self) -> Option<SpellArea> {
+        self.area
+    }
+
+    pub fn attack(&self) -> bool {
+        self.attack
+    }
+
+    pub fn saving_throw(&self) -> Option<String> {
+        self.saving_throw.clone()
+    }
+
+    ///Chosen with a map cursor, rather than cast on the caster
+    pub fn is_targeted(&self) -> bool {
+        self.range > 0 || self.area.is_some()
+    }
+
+    ///Damage dice, cantrips gaining dice as the caster levels
+    pub fn damage_dice(
+        &self,
+        caster_level: u32,
+    ) -> Option<Dice> {
+        let mut dice = Dice::parse(self.damage.as_ref()?.split(' ').next()?)?;
+        if self.is_cantrip() {
+            dice.count *= 1 + CANTRIP_LEVELS
+                .iter()
+                .filter(|&&level| caster_level >= level)
+                .count() as u32;
+        }
+        Some(dice)
+    }
+
+    pub fn damage_type(&self) -> String {
+        self.damage
+            .as_ref()
+            .and_then(|damage| damage.split(' ').nth(1))
+            .unwrap_or("")
+            .to_string()
+    }
+
+    pub fn healing_dice(&self) -> Option<Dice> {
+        Dice::parse(self.healing.as_ref()?)
+    }
+
+    pub fn inflicts(&self) -> Option<&Inflicts> {
+        self.inflicts.as_ref()
+    }
+
+    ///Points the spell covers when cast from one point at another
+    pub fn area_points(
+        &self,
+        caster: Point,
+        target: Point,
+    ) -> Vec<Point> {
+        let area = match self.area {
+            Some(area) => area,
+            None => return vec![target],
+        };
+        let size = (area.size / FEET_PER_TILE) as i32;
+        let (cx, cy) = (caster.col as i32, caster.row as i32);
+        let (tx, ty) = (target.col as i32, target.row as i32);
+        let (vx, vy) = (tx - cx, ty - cy);
+
+        let points: Vec<(i32, i32)> = match area.shape {
+            SpellShape::Sphere => (ty - size..=ty + size)
+                .flat_map(|y| (tx - size..=tx + size).map(move |x| (x, y)))
+                .collect(),
+            SpellShape::Cube => {
+                let half = size / 2;
+                (ty - half..=ty + half)
+                    .flat_map(|y| (tx - half..=tx + half).map(move |x| (x, y)))
+                    .collect()
+            }
+            SpellShape::Cone => {
+                let length = ((vx * vx + vy * vy) as f32).sqrt();
+                (cy - size..=cy + size)
+                    .flat_map(|y| (cx - size..=cx + size).map(move |x| (x, y)))
+                    .filter(|&(x, y)| {
+                        let (wx, wy) = (x - cx, y - cy);
+                        let distance = ((wx * wx + wy * wy) as f32).sqrt();
+                        //Half the width either side of the middle over the length
+                        length > 0.0
+                            && distance > 0.0
+                            && (wx.abs().max(wy.abs())) <= size
+                            && (vx * wx + vy * wy) as f32 / (length * distance) >= 0.89
+                    })
+                    .collect()
+            }
+            SpellShape::Line => {
+                let steps = vx.abs().max(vy.abs());
+                if steps == 0 {
+                    Vec::new()
+                } else {
+                    let end = Point {
+                        col: (cx + vx * size / steps).max(0) as usize,
+                        row: (cy + vy * size / steps).max(0) as usize,
+                    };
+                    vec_between_points(&caster, &end)
+                        .iter()
+                        .map(|p| (p.col as i32, p.row as i32))
+                        .collect()
+                }
+            }
+        };
+        points
+            .into_iter()
+            .filter(|&(x, y)| x >= 0 && y >= 0 && (x, y) != (cx, cy))
+            .map(|(x, y)| Point {
+                col: x as usize,
+                row: y as usize,
+            })
+            .collect()
+    }
+
+    ///Where the spell spreads from, walls in between stop it
+    pub fn origin(
+        &self,
+        caster: Point,
+        target: Point,
+    ) -> Point {
+        match self.area {
+            Some(area) if area.shape == SpellShape::Sphere || area.shape == SpellShape::Cube => {
+                target
+            }
+            _ => caster,
+        }
+    }
+
+    pub fn detail_name(&self) -> String {
+        format!("{}\n", self.spell.to_case(Case::Title))
+    }
+
+    ///Level, range, area and effect on one line
+    pub fn summary(&self) -> String {
+        let mut summary = vec![if self.is_cantrip() {
+            format!("{} cantrip", self.school.to_case(Case::Title))
+        } else {
+            format!("Level {} {}", self.level, self.school)
+        }];
+        summary.push(match self.range {
+            0 => "self".to_string(),
+            5 => "touch".to_string(),
+            range => format!("range {} ft", range),
+        });
+        if let Some(area) = self.area {
+            summary.push(format!("{} ft {:?}", area.size, area.shape).to_lowercase());
+        }
+        if self.attack {
+            summary.push("spell attack".to_string());
+        }
+        if let Some(saving_throw) = &self.saving_throw {
+            summary.push(format!("{} save", saving_throw));
+        }
+        if let Some(damage) = &self.damage {
+            summary.push(damage.clone());
+        }
+        if let Some(healing) = &self.healing {
+            summary.push(format!("heals {}", healing));
+        }
+        if let Some(inflicts) = &self.inflicts {
+            summary.push(format!(
+                "{} for {} s",
+                inflicts.condition.name(),
+                inflicts.duration
+            ));
+        }
+        summary.join(", ")
+    }
+
+    pub fn details(&self) -> String {
+        format!(
+            "{}\n{}\n\n{}",
+            self.detail_name(),
+            textwrap::fill(
+                &self.summary(),
+                textwrap::Options::new(COLUMN_WIDTH)
+                    .initial_indent("  ")
+                    .subsequent_indent("  ")
+            ),
+            textwrap::fill(
+                &self.description,
+                textwrap::Options::new(COLUMN_WIDTH)
+                    .initial_indent("  ")
+                    .subsequent_indent("  ")
+            )
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Spells {
+    spells: Vec<Spell>,
+}
+
+impl Default for Spells {
+    fn default() -> Self {
+        Spells::new()
+    }
+}
+
+impl Spells {
+    pub fn new() -> Spells {
+        let spell_json = include_str!("spells.json");
+        let spells: Vec<Spell> = serde_json::from_str(spell_json).unwrap();
+        Spells { spells }
+    }
+
+    pub fn value(
+        &self,
+        key: &str,
+    ) -> Option<&Spell> {
+        self.spells.iter().find(|spell| spell.spell == key)
+    }
+
+    ///Spells on a class list, up to a spell level
+    pub fn keys_for_class(
+        &self,
+        class: &str,
+        max_level: u32,
+    ) -> Vec<String> {
+        self.spells
+            .iter()
+            .filter(|spell| spell.level <= max_level && spell.classes.iter().any(|c| c == class))
+            .map(|spell| spell.spell.clone())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spell(name: &str) -> Spell {
+        Spells::new().value(name).unwrap().clone()
+    }
+
+    fn p(
+        col: usize,
+        row: usize,
+    ) -> Point {
+        Point { col, row }
+    }
+
+    #[test]
+    fn cantrips_gain_dice_with_caster_level() {
+        let fire_bolt = spell("fire bolt");
+        let counts: Vec<u32> = [1, 4, 5, 10, 11, 16, 17, 20]
+            .iter()
+            .map(|&level| fire_bolt.damage_dice(level).unwrap().count)
+            .collect();
+        assert_eq!(counts, vec![1, 1, 2, 2, 3, 3, 4, 4]);
+    }
+
+    #[test]
+    fn levelled_spells_keep_their_dice() {
+        let shatter = spell("shatter");
+        assert_eq!(shatter.damage_dice(1).unwrap().count, 3);
+        assert_eq!(shatter.damage_dice(20).unwrap().count, 3);
+        assert!(spell("cure wounds").damage_dice(1).is_none());
+    }
+
+    #[test]
+    fn single_target_covers_only_the_target() {
+        assert_eq!(
+            spell("fire bolt").area_points(p(1, 1), p(8, 3)),
+            vec![p(8, 3)]
+        );
+    }
+
+    #[test]
+    fn sphere_covers_the_radius_around_the_target() {
+        //10 feet is two tiles either side
+        let points = spell("shatter").area_points(p(0, 0), p(10, 10));
+        assert_eq!(points.len(), 25);
+        assert!(points.contains(&p(8, 8)));
+        assert!(points.contains(&p(12, 12)));
+        assert!(!points.contains(&p(13, 10)));
+    }
+
+    #[test]
+    fn cube_leaves_out_the_caster() {
+        //15 feet is three tiles across
+        let points = spell("thunderwave").area_points(p(10, 10), p(11, 10));
+        assert_eq!(points.len(), 8);
+        assert!(!points.contains(&p(10, 10)));
+        assert!(points.contains(&p(12, 11)));
+    }
+
+    #[test]
+    fn cone_widens_away_from_the_caster() {
+        let points = spell("burning hands").area_points(p(10, 10), p(13, 10));
+        assert!(points.contains(&p(11, 10)));
+        assert!(points.contains(&p(13, 10)));
+        assert!(points.contains(&p(13, 11)));
+        assert!(!points.contains(&p(11, 12)));
+        assert!(points.iter().all(|point| point.col > 10 && point.col <= 13));
+    }
+
+    #[test]
+    fn line_runs_its_full_length_from_the_caster() {
+        //100 feet is twenty tiles
+        let points = spell("lightning bolt").area_points(p(5, 5), p(7, 5));
+        assert_eq!(points.len(), 20);
+        assert!(points.iter().all(|point| point.row == 5));
+        assert!(points.contains(&p(25, 5)));
+        assert!(!points.contains(&p(5, 5)));
+    }
+}
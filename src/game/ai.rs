@@ -0,0 +1,380 @@
+use crate::actor::conditions::Condition;
+use crate::actor::creatures::Behaviour;
+use crate::actor::energy::Action;
+use crate::game::combat::creature_attack;
+use crate::game::messages::{Category, Severity};
+use crate::game::Game;
+use crate::levels::Level;
+use crate::utils::*;
+use rand::Rng;
+
+///How far from its post a guard follows the player, in tiles
+const GUARD_RANGE: usize = 8;
+
+///How far a roaming creature sets off at a time, in tiles
+const WANDER_RANGE: i32 = 10;
+
+///How far a pack creature's call carries, in tiles
+const PACK_CALL_RANGE: usize = 12;
+
+///How far off a creature spots the player by the light of their torch, in tiles
+const SIGHT_RANGE: usize = 20;
+
+///Chance in 3 that a roaming creature with nowhere to go rests a turn
+const REST_CHANCE: u32 = 1;
+
+///Take the turn of the creature at an index on the current level, as its
+///behaviour and what it knows of the player lead it to
+///
+///Returns what the creature spent its turn on
+pub fn creature_act(
+    game: &mut Game,
+    index: usize,
+) -> Action {
+    let player = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+    notice(game, index, player);
+
+    let level = game.levels.level(game.position.location());
+    let position = level.creatures[index].position;
+    let aware = level.creatures[index].aware;
+    let in_reach = distance(&position, &player) == 1 && level.can_move_to(player, position);
+    let conditions = &mut level.creatures[index].conditions;
+    if !conditions.can_act() {
+        return Action::Wait;
+    }
+    //Standing up takes its move
+    if conditions.remove(Condition::Prone) {
+        return Action::Move;
+    }
+    let can_attack = conditions.can_attack();
+
+    if aware && level.creatures[index].is_fleeing() {
+        if let Some(next) = flee_step(level, index, player) {
+            level.creatures[index].position = next;
+            return Action::Move;
+        }
+        //Cornered, it fights
+        if !in_reach || !can_attack {
+            return Action::Wait;
+        }
+    }
+    if aware && in_reach && can_attack {
+        creature_attack(game, index);
+        return Action::Attack;
+    }
+    if aware && can_attack {
+        if let Some(action) = chase(level, index, player) {
+            return action;
+        }
+    }
+    idle(level, index, player)
+}
+
+///Let the creature see the player if they are in its sight, and remember
+///where it saw them
+fn notice(
+    game: &mut Game,
+    index: usize,
+    player: Point,
+) {
+    let level = game.levels.level(game.position.location());
+    let creature = &level.creatures[index];
+    //Blinded, it only notices the player right next to it
+    let sight = creature
+        .conditions
+        .sight_limit()
+        .map_or(SIGHT_RANGE, |limit| limit.min(SIGHT_RANGE));
+    let seen = distance(&creature.position, &player) <= sight
+        && level.is_line_clear(creature.position, player);
+    let creature = &mut level.creatures[index];
+    //Saved before creatures had a home
+    if creature.home == Point::default() {
+        creature.home = creature.position;
+    }
+    if !seen {
+        //Hurt by something it could not see, it goes to where the attack came from
+        if creature.aware && creature.last_seen.is_none() {
+            creature.last_seen = Some(player);
+        }
+        return;
+    }
+    //A guard keeps an eye on the player but does not leave its post for them
+    if creature.behaviour() == Behaviour::Guard && distance(&player, &creature.home) > GUARD_RANGE {
+        return;
+    }
+    creature.last_seen = Some(player);
+    if creature.aware {
+        return;
+    }
+    creature.aware = true;
+    creature.destination = None;
+    let creature = creature.clone();
+
+    //A pack creature calls the others of its kind nearby
+    let mut called = false;
+    if creature.behaviour() == Behaviour::Pack {
+        for other in level.creatures.iter_mut().filter(|other| {
+            other.name() == creature.name()
+                && !other.aware
+                && distance(&other.position, &creature.position) <= PACK_CALL_RANGE
+        }) {
+            other.aware = true;
+            other.last_seen = Some(player);
+            other.destination = None;
+            called = true;
+        }
+    }
+    game.message(
+        Severity::Warning,
+        Category::Combat,
+        &format!("The {} notices you", creature.name()),
+    );
+    if called {
+        game.message(
+            Severity::Warning,
+            Category::Combat,
+            &format!("The {} calls to its pack", creature.name()),
+        );
+    }
+}
+
+///Close in on the player, or on where the player was last seen
+///
+///Returns None once the creature has lost track of the player
+fn chase(
+    level: &mut Level,
+    index: usize,
+    player: Point,
+) -> Option<Action> {
+    let creature = &level.creatures[index];
+    let position = creature.position;
+    let target = match creature.last_seen {
+        Some(target) => target,
+        None => player,
+    };
+    //A guard does not stray far from its post, and a creature that reaches
+    //the last place it saw the player has nothing more to go on
+    if (creature.behaviour() == Behaviour::Guard && distance(&target, &creature.home) > GUARD_RANGE)
+        || position == target
+    {
+        let creature = &mut level.creatures[index];
+        creature.aware = false;
+        creature.last_seen = None;
+        return None;
+    }
+
+    //A pack spreads out to the free tiles around the player rather than
+    //queueing up behind each other
+    let path = if creature.behaviour() == Behaviour::Pack && target == player {
+        let level_ref: &Level = level;
+        level_ref.find_path_to_nearest(position, false, |p| {
+            distance(&p, &player) == 1
+                && level_ref.can_move_to(player, p)
+                && level_ref.creature_at(p).is_none()
+        })
+    } else {
+        None
+    };
+    let path = path.or_else(|| level.find_path(position, target, false));
+    Some(step(level, index, path, player))
+}
+
+///Roam, keep to a post or wait, as the creature does when it has not noticed
+///the player
+fn idle(
+    level: &mut Level,
+    index: usize,
+    player: Point,
+) -> Action {
+    let creature = &level.creatures[index];
+    let position = creature.position;
+    match creature.behaviour() {
+        Behaviour::Hunt => Action::Wait,
+        Behaviour::Guard => {
+            if position == creature.home {
+                return Action::Wait;
+            }
+            let path = level.find_path(position, creature.home, false);
+            step(level, index, path, player)
+        }
+        Behaviour::Wander | Behaviour::Pack => {
+            let mut rng = rand::thread_rng();
+            let destination = match creature.destination {
+                Some(destination) if destination != position => destination,
+                _ => {
+                    level.creatures[index].destination = None;
+                    if rng.gen_range(1..=3) <= REST_CHANCE {
+                        return Action::Wait;
+                    }
+                    match wander_destination(level, position) {
+                        Some(destination) => destination,
+                        None => return Action::Wait,
+                    }
+                }
+            };
+            level.creatures[index].destination = Some(destination);
+            let path = level.find_path(position, destination, false);
+            if path.is_none() {
+                level.creatures[index].destination = None;
+            }
+            step(level, index, path, player)
+        }
+    }
+}
+
+///A random open tile not far from a point
+fn wander_destination(
+    level: &Level,
+    from: Point,
+) -> Option<Point> {
+    let mut rng = rand::thread_rng();
+    for _ in 0..20 {
+        let col = from.col as i32 + rng.gen_range(-WANDER_RANGE..=WANDER_RANGE);
+        let row = from.row as i32 + rng.gen_range(-WANDER_RANGE..=WANDER_RANGE);
+        if !level.is_open(col, row) {
+            continue;
+        }
+        let p = Point {
+            col: col as usize,
+            row: row as usize,
+        };
+        if p != from && level.creature_at(p).is_none() {
+            return Some(p);
+        }
+    }
+    None
+}
+
+///The free tile next to a creature that takes it furthest from the player,
+///None if no tile is further than where it stands
+fn flee_step(
+    level: &Level,
+    index: usize,
+    player: Point,
+) -> Option<Point> {
+    let creature = &level.creatures[index];
+    if creature.movement_speed() == 0 {
+        return None;
+    }
+    let position = creature.position;
+    level
+        .neighbours(position, false)
+        .into_iter()
+        .filter(|&p| p != player && level.creature_at(p).is_none())
+        .filter(|p| distance(p, &player) > distance(&position, &player))
+        .max_by_key(|p| distance(p, &player))
+}
+
+///Take the first step along a path, waiting if something is in the way
+fn step(
+    level: &mut Level,
+    index: usize,
+    path: Option<Vec<Point>>,
+    player: Point,
+) -> Action {
+    let next = match path.as_ref().and_then(|path| path.first()) {
+        Some(&next) => next,
+        None => return Action::Wait,
+    };
+    if next == player
+        || level.creature_at(next).is_some()
+        || level.creatures[index].movement_speed() == 0
+    {
+        return Action::Wait;
+    }
+    level.creatures[index].position = next;
+    Action::Move
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::creatures::Creatures;
+
+    fn p(
+        col: usize,
+        row: usize,
+    ) -> Point {
+        Point { col, row }
+    }
+
+    ///Level with creatures placed at positions, aware of the player
+    fn level_with(
+        rows: &[&str],
+        creatures: &[(&str, Point)],
+    ) -> Level {
+        let mut level = Level::from_rows(rows);
+        for &(key, position) in creatures {
+            let mut creature = Creatures::new().spawn(key, position).unwrap();
+            creature.aware = true;
+            level.creatures.push(creature);
+        }
+        level
+    }
+
+    #[test]
+    fn fleeing_steps_away_from_the_player() {
+        let level = level_with(
+            &["#######", "#.....#", "#.....#", "#######"],
+            &[("kobold", p(3, 1))],
+        );
+        let next = flee_step(&level, 0, p(2, 1)).unwrap();
+        assert_eq!(distance(&next, &p(2, 1)), 2);
+    }
+
+    #[test]
+    fn cornered_creature_has_nowhere_to_flee() {
+        let level = level_with(&["#####", "#...#", "#####"], &[("kobold", p(3, 1))]);
+        assert_eq!(flee_step(&level, 0, p(2, 1)), None);
+    }
+
+    #[test]
+    fn guard_gives_up_past_its_leash() {
+        let mut level = level_with(
+            &[
+                "#################",
+                "#...............#",
+                "#################",
+            ],
+            &[("skeleton", p(2, 1))],
+        );
+        level.creatures[0].home = p(1, 1);
+        level.creatures[0].last_seen = Some(p(1 + GUARD_RANGE + 1, 1));
+        assert_eq!(chase(&mut level, 0, p(15, 1)), None);
+        assert!(!level.creatures[0].aware);
+
+        level.creatures[0].aware = true;
+        level.creatures[0].last_seen = Some(p(1 + GUARD_RANGE, 1));
+        assert_eq!(chase(&mut level, 0, p(15, 1)), Some(Action::Move));
+        assert_eq!(level.creatures[0].position, p(3, 1));
+    }
+
+    #[test]
+    fn creature_loses_track_where_it_last_saw_the_player() {
+        let mut level = level_with(&["#######", "#.....#", "#######"], &[("goblin", p(3, 1))]);
+        level.creatures[0].last_seen = Some(p(3, 1));
+        assert_eq!(chase(&mut level, 0, p(5, 1)), None);
+        assert!(!level.creatures[0].aware);
+        assert_eq!(level.creatures[0].last_seen, None);
+    }
+
+    #[test]
+    fn pack_spreads_out_around_the_player() {
+        //The straight way to the player is through the first kobold, the
+        //second takes the free tile beside it instead
+        let mut level = level_with(
+            &["#######", "#.....#", "#.....#", "#######"],
+            &[("kobold", p(4, 1)), ("kobold", p(3, 1))],
+        );
+        let player = p(5, 1);
+        for creature in level.creatures.iter_mut() {
+            creature.last_seen = Some(player);
+        }
+        assert_eq!(chase(&mut level, 1, player), Some(Action::Move));
+        assert_eq!(level.creatures[1].position, p(4, 2));
+        assert_eq!(level.creatures[0].position, p(4, 1));
+    }
+}
@@ -0,0 +1,304 @@
+use crate::actor::energy::Action;
+use crate::actor::player::classes::Spellcasting;
+use crate::actor::skills::Check;
+use crate::actor::spells::Spell;
+use crate::actor::{ability_key, ability_modifier};
+use crate::game::combat::{attack_hits, damage_creature};
+use crate::game::map::select_target;
+use crate::game::messages::{Category, Severity};
+use crate::game::Game;
+use crate::levels::Level;
+use crate::screen::Screen;
+use crate::utils::*;
+use convert_case::{Case, Casing};
+
+///Choose one of the character's spells and cast it
+pub fn cast_spell(
+    game: &mut Game,
+    screen: &mut Screen,
+) {
+    let spellcasting = match game.character.spellcasting(&game.classes) {
+        Some(spellcasting) => spellcasting,
+        None => {
+            game.message(
+                Severity::Info,
+                Category::General,
+                "You do not know how to cast spells",
+            );
+            return;
+        }
+    };
+    let known: Vec<Spell> = game
+        .character
+        .spells
+        .iter()
+        .filter_map(|spell| game.spells.value(spell))
+        .cloned()
+        .collect();
+    if known.is_empty() {
+        game.message(
+            Severity::Info,
+            Category::General,
+            "You have no spells ready to cast",
+        );
+        return;
+    }
+
+    let names: Vec<String> = known
+        .iter()
+        .map(|spell| {
+            if spell.is_cantrip() {
+                format!("{} (cantrip)", spell.name().to_case(Case::Title))
+            } else {
+                format!(
+                    "{} (level {}, {} slots)",
+                    spell.name().to_case(Case::Title),
+                    spell.level(),
+                    game.character
+                        .spell_slots_left(&game.classes, spell.level())
+                )
+            }
+        })
+        .collect();
+    let original_header = screen.get_header();
+    let original_msg = screen.get_msg();
+    screen.set_header("Cast a Spell");
+    let chosen = screen.draw_menu("Choose spell to cast", &names, &|index| {
+        known[index].details()
+    });
+    screen.set_header(&original_header);
+    screen.set_msg(&original_msg);
+
+    let spell = match chosen {
+        Some(index) => known[index].clone(),
+        None => return,
+    };
+    let name = spell.name().to_case(Case::Title);
+    if !spell.is_cantrip()
+        && (spell.level()..=9)
+            .all(|level| game.character.spell_slots_left(&game.classes, level) == 0)
+    {
+        game.message(
+            Severity::Warning,
+            Category::General,
+            &format!("You have no spell slots left to cast {}, rest first", name),
+        );
+        return;
+    }
+
+    let player = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+    let target = if spell.is_targeted() {
+        let area = |level: &Level, cursor: Point| {
+            spell
+                .area_points(player, cursor)
+                .into_iter()
+                .filter(|&p| level.is_line_clear(spell.origin(player, cursor), p))
+                .collect()
+        };
+        match select_target(game, screen, &format!("Cast {}", name), &area) {
+            Some(target) => target,
+            None => return,
+        }
+    } else {
+        player
+    };
+    if let Err(reason) = check_target(game, &spell, player, target) {
+        game.message(Severity::Warning, Category::General, &reason);
+        return;
+    }
+    if spell.damage_dice(1).is_some() && !game.character.conditions.can_attack() {
+        game.message(
+            Severity::Warning,
+            Category::Combat,
+            "You can not bring yourself to attack",
+        );
+        return;
+    }
+
+    let slot_level = if spell.is_cantrip() {
+        None
+    } else {
+        game.character
+            .spend_spell_slot(&game.classes, spell.level())
+    };
+    game.take_action(Action::Cast);
+    let cast = match slot_level {
+        Some(slot_level) if slot_level > spell.level() => {
+            format!("You cast {} with a level {} slot", name, slot_level)
+        }
+        _ => format!("You cast {}", name),
+    };
+    game.message(Severity::Info, Category::Combat, &cast);
+
+    if let Some(dice) = spell.healing_dice() {
+        let hit_points = (dice.roll() as i32 + spell_modifier(game, &spellcasting)).max(1) as u32;
+        game.character.heal(hit_points);
+        game.message(
+            Severity::Good,
+            Category::Combat,
+            &format!("You regain {} hit points", hit_points),
+        );
+    }
+
+    if spell.damage_dice(1).is_some() {
+        cast_damage(game, &spell, player, target);
+    }
+}
+
+///Modifier of the ability the character casts with
+fn spell_modifier(
+    game: &Game,
+    spellcasting: &Spellcasting,
+) -> i32 {
+    ability_modifier(
+        game.character
+            .abilities
+            .ability(ability_key(&spellcasting.ability)),
+    )
+}
+
+///Check the target is in range, in view and not behind a wall
+fn check_target(
+    game: &mut Game,
+    spell: &Spell,
+    player: Point,
+    target: Point,
+) -> Result<(), String> {
+    if !spell.is_targeted() {
+        return Ok(());
+    }
+    let level = game.levels.level(game.position.location());
+    if spell.range() == 0 {
+        if target == player {
+            return Err("Choose a direction away from yourself".to_string());
+        }
+        return Ok(());
+    }
+    if distance(&player, &target) > spell.range_tiles().max(1) {
+        return Err(format!(
+            "That is out of range, {} reaches {} ft",
+            spell.name().to_case(Case::Title),
+            spell.range()
+        ));
+    }
+    if target != player && (!level.is_visible(target) || !level.is_line_clear(player, target)) {
+        return Err("You can not see a clear path there".to_string());
+    }
+    Ok(())
+}
+
+///Deal a spell's damage to every creature it reaches
+fn cast_damage(
+    game: &mut Game,
+    spell: &Spell,
+    player: Point,
+    target: Point,
+) {
+    let location = game.position.location();
+    let caster_level = game.character.level();
+    let dice = spell.damage_dice(caster_level).unwrap_or_default();
+    let damage_type = spell.damage_type();
+    let attack_modifier = game.character.spell_attack_modifier(&game.classes);
+    let save_dc = game.character.spell_save_dc(&game.classes);
+    let hindered = game.character.conditions.attack_disadvantage();
+
+    let level = game.levels.level(location);
+    let origin = spell.origin(player, target);
+    let points = spell.area_points(player, target);
+    let mut targets: Vec<usize> = level
+        .creatures
+        .iter()
+        .enumerate()
+        .filter(|(_, creature)| {
+            points.contains(&creature.position) && level.is_line_clear(origin, creature.position)
+        })
+        .map(|(index, _)| index)
+        .collect();
+    if targets.is_empty() {
+        game.message(Severity::Info, Category::Combat, "The spell hits nothing");
+        return;
+    }
+
+    //Last first, so removing the dead leaves the other indexes in place
+    targets.sort_unstable_by(|a, b| b.cmp(a));
+    for index in targets {
+        let creature = game.levels.level(location).creatures[index].clone();
+        let mut damage = dice.roll();
+        let mut saved = false;
+
+        if spell.attack() {
+            let (exposed, guarded) = creature
+                .conditions
+                .attacked(distance(&player, &creature.position));
+            let roll = d20(!creature.aware || exposed, hindered || guarded);
+            if !attack_hits(roll, attack_modifier, creature.armor_class()) {
+                game.levels.level(location).creatures[index].aware = true;
+                game.message(
+                    Severity::Info,
+                    Category::Combat,
+                    &format!("The spell misses the {}", creature.name()),
+                );
+                continue;
+            }
+            if roll == 20 {
+                damage += dice.roll_dice();
+            }
+        }
+
+        if let Some(ability) = spell.saving_throw() {
+            saved = !creature.conditions.fails_save(&ability)
+                && Check::roll_with(
+                    creature.saving_throw_modifier(&ability),
+                    save_dc.max(0) as u32,
+                    false,
+                    creature.conditions.save_disadvantage(&ability),
+                )
+                .success();
+            if saved {
+                //Areas still catch part of a creature that saves
+                if spell.area().is_some() {
+                    damage /= 2;
+                } else {
+                    damage = 0;
+                }
+            }
+        }
+
+        if damage == 0 {
+            game.levels.level(location).creatures[index].aware = true;
+            game.message(
+                Severity::Info,
+                Category::Combat,
+                &format!("The {} resists the spell", creature.name()),
+            );
+            continue;
+        }
+        game.message(
+            Severity::Good,
+            Category::Combat,
+            &format!(
+                "The {} takes {} {} damage",
+                creature.name(),
+                damage,
+                damage_type
+            ),
+        );
+        if let Some(inflicts) = spell.inflicts() {
+            if !saved
+                && game.levels.level(location).creatures[index]
+                    .conditions
+                    .add(inflicts.condition, game.time + inflicts.duration)
+            {
+                game.message(
+                    Severity::Good,
+                    Category::Combat,
+                    &format!("The {} is {}", creature.name(), inflicts.condition.name()),
+                );
+            }
+        }
+        damage_creature(game, index, damage);
+    }
+}
@@ -0,0 +1,877 @@
+use crate::actor::ability_modifier;
+use crate::actor::energy::Action;
+use crate::actor::spells::FEET_PER_TILE;
+use crate::game::ai::creature_act;
+use crate::game::map::select_target;
+use crate::game::messages::{Category, Severity};
+use crate::game::scheduler::Actor;
+use crate::game::Game;
+use crate::items::weapons::Weapon;
+use crate::screen::Screen;
+use crate::utils::*;
+use convert_case::{Case, Casing};
+use rand::seq::SliceRandom;
+
+///Damage types a raging barbarian resists
+const RAGE_RESISTANCES: [&str; 3] = ["bludgeoning", "piercing", "slashing"];
+
+///Seconds a short rest takes
+const SHORT_REST_SECONDS: u32 = 60 * 60;
+
+///Seconds a long rest takes
+const LONG_REST_SECONDS: u32 = 8 * 60 * 60;
+
+///A natural 20 always hits and a natural 1 always misses
+pub fn attack_hits(
+    roll: u32,
+    modifier: i32,
+    armor_class: i32,
+) -> bool {
+    roll == 20 || (roll != 1 && roll as i32 + modifier >= armor_class)
+}
+
+///Damage dice of a weapon, an unarmed strike without one
+///
+///Versatile weapons hit harder with both hands, the off hand empty
+fn attack_dice(
+    weapon: Option<&Weapon>,
+    two_handed: bool,
+) -> Dice {
+    match weapon {
+        Some(weapon) => match weapon.versatile_dice() {
+            Some(dice) if two_handed => dice,
+            _ => weapon.damage_dice(),
+        },
+        None => Dice {
+            bonus: 1,
+            ..Default::default()
+        },
+    }
+}
+
+///Check an attack is made with strength rather than dexterity
+///
+///Ranged attacks use dexterity unless the weapon is thrown, and melee attacks
+///strength, while finesse weapons use whichever is better
+fn uses_strength(
+    finesse: bool,
+    ranged: bool,
+    thrown: bool,
+    strength: i32,
+    dexterity: i32,
+) -> bool {
+    if finesse {
+        strength >= dexterity
+    } else {
+        !ranged || thrown
+    }
+}
+
+///Ability modifier added to damage, the off hand only adding a penalty
+fn damage_modifier(
+    ability: i32,
+    off_hand: bool,
+) -> i32 {
+    if off_hand {
+        ability.min(0)
+    } else {
+        ability
+    }
+}
+
+///Heavy weapons are unwieldy for small folk
+fn unwieldy(
+    weapon: Option<&Weapon>,
+    small: bool,
+) -> bool {
+    small && weapon.is_some_and(|weapon| weapon.heavy())
+}
+
+///Extra damage from rage, only for strength melee attacks while raging
+fn rage_damage(
+    bonus: u32,
+    raging: bool,
+    ranged: bool,
+    uses_strength: bool,
+) -> i32 {
+    if raging && !ranged && uses_strength {
+        bonus as i32
+    } else {
+        0
+    }
+}
+
+///Damage left after resistance, from the race or from raging, halves it
+fn resisted_damage(
+    damage: u32,
+    damage_type: &str,
+    race_resistant: bool,
+    raging: bool,
+) -> u32 {
+    if race_resistant || (raging && RAGE_RESISTANCES.contains(&damage_type)) {
+        damage / 2
+    } else {
+        damage
+    }
+}
+
+///Attack the creature at an index on the current level with the wielded
+///weapon, following up with the off hand when both weapons are light
+pub fn player_attack(
+    game: &mut Game,
+    index: usize,
+) {
+    let location = game.position.location();
+    let position = game.levels.level(location).creatures[index].position;
+    let main_hand = game.character.main_hand().cloned();
+    weapon_attack(game, index, main_hand.as_ref(), false, false, false);
+
+    //Two light melee weapons give one extra attack a turn
+    let off_hand = match game.character.off_hand() {
+        Some(weapon) => weapon.clone(),
+        None => return,
+    };
+    let light = |weapon: &Weapon| weapon.light() && weapon.class() == "melee";
+    if game.off_hand_attacked || !light(&off_hand) || !main_hand.as_ref().is_some_and(light) {
+        return;
+    }
+    if let Some(index) = game.levels.level(location).creature_at(position) {
+        game.off_hand_attacked = true;
+        weapon_attack(game, index, Some(&off_hand), false, false, true);
+    }
+}
+
+///Attack a chosen creature within reach of the wielded weapon
+pub fn reach_attack(
+    game: &mut Game,
+    screen: &mut Screen,
+) {
+    let reach = if game
+        .character
+        .main_hand()
+        .is_some_and(|weapon| weapon.reach())
+    {
+        2
+    } else {
+        1
+    };
+    let player = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+    let target = match select_target(game, screen, "Attack", &|_, _| Vec::new()) {
+        Some(target) => target,
+        None => return,
+    };
+
+    let level = game.levels.level(game.position.location());
+    let index = match level.creature_at(target) {
+        Some(index)
+            if distance(&player, &target) <= reach
+                && level.is_visible(target)
+                && level.is_line_clear(player, target) =>
+        {
+            index
+        }
+        Some(_) => {
+            game.message(
+                Severity::Warning,
+                Category::Combat,
+                &format!(
+                    "That is out of reach, you reach {} ft",
+                    reach as u32 * FEET_PER_TILE
+                ),
+            );
+            return;
+        }
+        None => {
+            game.message(
+                Severity::Info,
+                Category::Combat,
+                "There is nothing there to attack",
+            );
+            return;
+        }
+    };
+    player_attack(game, index);
+    game.take_action(Action::Attack);
+}
+
+///Choose the weapons held in the main and off hand, takes no time
+pub fn wield(
+    game: &mut Game,
+    screen: &mut Screen,
+) {
+    let weapons = game.character.weapons.clone();
+    if weapons.is_empty() {
+        game.message(
+            Severity::Info,
+            Category::General,
+            "You have no weapons to wield",
+        );
+        return;
+    }
+
+    let names: Vec<String> = weapons
+        .iter()
+        .map(|weapon| weapon.name().to_case(Case::Title))
+        .collect();
+    let original_header = screen.get_header();
+    let original_msg = screen.get_msg();
+    screen.set_header("Wield Weapons");
+    let main_hand = screen.draw_menu("Choose weapon for the main hand", &names, &|index| {
+        weapons[index].details()
+    });
+
+    //A second one handed weapon can go in the off hand
+    let off_hand = match main_hand {
+        Some(main_hand) if !weapons[main_hand].two_handed() => {
+            let others: Vec<usize> = (0..weapons.len())
+                .filter(|&index| index != main_hand && !weapons[index].two_handed())
+                .collect();
+            let mut names = vec!["Nothing".to_string()];
+            names.extend(
+                others
+                    .iter()
+                    .map(|&index| weapons[index].name().to_case(Case::Title)),
+            );
+            if others.is_empty() {
+                Some(None)
+            } else {
+                screen
+                    .draw_menu("Choose weapon for the off hand", &names, &|index| {
+                        if index == 0 {
+                            "Keep the off hand free, versatile weapons are then held in both hands"
+                                .to_string()
+                        } else {
+                            weapons[others[index - 1]].details()
+                        }
+                    })
+                    .map(|index| index.checked_sub(1).map(|index| others[index]))
+            }
+        }
+        _ => Some(None),
+    };
+    screen.set_header(&original_header);
+    screen.set_msg(&original_msg);
+
+    let (main_hand, off_hand) = match (main_hand, off_hand) {
+        (Some(main_hand), Some(off_hand)) => (main_hand, off_hand),
+        _ => return,
+    };
+    game.character.wield(main_hand, off_hand);
+    game.message(
+        Severity::Info,
+        Category::General,
+        &match off_hand {
+            Some(off_hand) => format!(
+                "You wield the {} and the {}",
+                weapons[main_hand].name(),
+                weapons[off_hand].name()
+            ),
+            None => format!("You wield the {}", weapons[main_hand].name()),
+        },
+    );
+}
+
+///Attack the creature at an index on the current level with a weapon, or
+///unarmed without one
+pub fn weapon_attack(
+    game: &mut Game,
+    index: usize,
+    weapon: Option<&Weapon>,
+    ranged: bool,
+    disadvantage: bool,
+    off_hand: bool,
+) {
+    if !game.character.conditions.can_attack() {
+        game.message(
+            Severity::Warning,
+            Category::Combat,
+            "You can not bring yourself to attack",
+        );
+        return;
+    }
+    let location = game.position.location();
+    let level_number = game.character.level();
+    let player = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+    let two_handed = !ranged && !off_hand && game.character.off_hand().is_none();
+    let dice = attack_dice(weapon, two_handed);
+
+    //Fight unarmed without a weapon
+    let (weapon_name, damage_type, proficient, finesse) = match weapon {
+        Some(weapon) => (
+            weapon.name(),
+            weapon.damage_type(),
+            game.character.is_weapon_proficient(
+                &game.races,
+                &game.classes,
+                &game.weapons,
+                &weapon.name(),
+            ),
+            weapon.finesse(),
+        ),
+        None => (
+            "unarmed strike".to_string(),
+            "bludgeoning".to_string(),
+            true,
+            false,
+        ),
+    };
+    let thrown = weapon.is_some_and(|weapon| weapon.thrown());
+    let strength = ability_modifier(game.character.abilities.strength);
+    let dexterity = ability_modifier(game.character.abilities.dexterity);
+    let uses_strength = uses_strength(finesse, ranged, thrown, strength, dexterity);
+    let ability = if uses_strength { strength } else { dexterity };
+    let to_hit = ability
+        + if proficient {
+            game.character.proficiency_bonus()
+        } else {
+            0
+        };
+    let disadvantage = disadvantage
+        || unwieldy(weapon, game.character.is_small(&game.races))
+        || game.character.conditions.attack_disadvantage();
+
+    let creature = &mut game.levels.level(location).creatures[index];
+    let (exposed, guarded) = creature
+        .conditions
+        .attacked(distance(&creature.position, &player));
+    //Attacking a creature that has not noticed you has advantage
+    let advantage = !creature.aware || exposed;
+    let disadvantage = disadvantage || guarded;
+    creature.aware = true;
+    let roll = d20(advantage, disadvantage);
+    let name = creature.name();
+
+    if !attack_hits(roll, to_hit, creature.armor_class()) {
+        game.message(
+            Severity::Info,
+            Category::Combat,
+            &format!("You miss the {}", name),
+        );
+        return;
+    }
+
+    let critical = roll == 20;
+    let mut damage = dice.roll() as i32 + damage_modifier(ability, off_hand);
+    if critical {
+        damage += dice.roll_dice() as i32;
+    }
+
+    if let Some(rage) = game.character.feature(&game.classes, "rage") {
+        let raging = game.character.is_feature_active("rage", game.time);
+        damage += rage_damage(rage.scaling(level_number), raging, ranged, uses_strength);
+    }
+
+    //Sneak attack needs advantage and a finesse or ranged weapon, and only
+    //lands once a round
+    if let Some(sneak_attack) = game.character.feature(&game.classes, "sneak attack") {
+        if advantage && !disadvantage && (finesse || (ranged && !thrown)) && !game.sneak_attacked {
+            game.sneak_attacked = true;
+            let sneak_dice = Dice {
+                count: sneak_attack.scaling(level_number),
+                sides: 6,
+                bonus: 0,
+            };
+            damage += sneak_dice.roll() as i32;
+            if critical {
+                damage += sneak_dice.roll_dice() as i32;
+            }
+        }
+    }
+
+    let damage = damage.max(0) as u32;
+    game.message(
+        Severity::Good,
+        Category::Combat,
+        &format!(
+            "You {} the {} with your {} for {} {} damage",
+            if critical { "critically hit" } else { "hit" },
+            name,
+            weapon_name,
+            damage,
+            damage_type
+        ),
+    );
+    damage_creature(game, index, damage);
+}
+
+///Hurt the creature at an index on the current level, removing it and giving
+///experience if it dies
+pub fn damage_creature(
+    game: &mut Game,
+    index: usize,
+    damage: u32,
+) {
+    let creature = &mut game.levels.level(game.position.location()).creatures[index];
+    creature.aware = true;
+    creature.take_damage(damage);
+    if !creature.is_dead() {
+        return;
+    }
+    let name = creature.name();
+    let experience = creature.experience();
+    game.levels
+        .level(game.position.location())
+        .creatures
+        .remove(index);
+    game.message(
+        Severity::Good,
+        Category::Combat,
+        &format!("The {} dies", name),
+    );
+    game.gain_experience(experience, &format!("defeating the {}", name));
+}
+
+///The creature at an index on the current level attacks the player, with one
+///of its attacks picked at random
+pub fn creature_attack(
+    game: &mut Game,
+    index: usize,
+) {
+    let location = game.position.location();
+    let creature = game.levels.level(location).creatures[index].clone();
+    let attack = match creature.attacks().choose(&mut rand::thread_rng()) {
+        Some(attack) => attack.clone(),
+        None => return,
+    };
+
+    let player = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+    let (exposed, guarded) = game
+        .character
+        .conditions
+        .attacked(distance(&creature.position, &player));
+    let roll = d20(
+        exposed,
+        guarded || creature.conditions.attack_disadvantage(),
+    );
+    if !attack_hits(roll, attack.to_hit, game.character.armor_class()) {
+        game.message(
+            Severity::Info,
+            Category::Combat,
+            &format!("The {} misses you", creature.name()),
+        );
+        return;
+    }
+
+    let dice = attack.damage_dice();
+    let mut damage = dice.roll();
+    if roll == 20 {
+        damage += dice.roll_dice();
+    }
+
+    let damage_type = attack.damage_type();
+    let damage = resisted_damage(
+        damage,
+        &damage_type,
+        game.races
+            .effects(&game.character.race)
+            .is_resistant(&damage_type),
+        game.character.is_feature_active("rage", game.time),
+    );
+
+    game.character.take_damage(damage);
+    game.message(
+        Severity::Danger,
+        Category::Combat,
+        &format!(
+            "The {} {} you with its {} for {} {} damage",
+            creature.name(),
+            if roll == 20 {
+                "critically hits"
+            } else {
+                "hits"
+            },
+            attack.attack,
+            damage,
+            damage_type
+        ),
+    );
+
+    //Some attacks leave more than a wound
+    if let Some(inflicts) = &attack.inflicts {
+        let condition = inflicts.condition;
+        let resisted = match &inflicts.save {
+            Some(ability) => game.character.resist_condition(
+                &game.races,
+                &game.classes,
+                condition,
+                ability,
+                inflicts.dc,
+            ),
+            None => false,
+        };
+        if resisted {
+            game.message(
+                Severity::Good,
+                Category::Combat,
+                &format!("You resist being {}", condition.name()),
+            );
+        } else if game
+            .character
+            .conditions
+            .add(condition, game.time + inflicts.duration)
+        {
+            game.message(
+                Severity::Danger,
+                Category::Combat,
+                &format!("You are {}", condition.name()),
+            );
+        }
+    }
+}
+
+///Take the turn of the creature with an id on the current level, and queue
+///its next one
+pub fn creature_turn(
+    game: &mut Game,
+    id: u32,
+) {
+    let level = game.levels.level(game.position.location());
+    //Killed since its turn was queued
+    let index = match level.creature_index(id) {
+        Some(index) => index,
+        None => return,
+    };
+    let speed = level.creatures[index].current_speed();
+    let name = level.creatures[index].name();
+    //Conditions wear off, noticed if the player can see it
+    let ended = level.creatures[index].conditions.expire(game.time);
+    if level.is_visible(level.creatures[index].position) {
+        for condition in ended {
+            game.message(
+                Severity::Info,
+                Category::Combat,
+                &format!("The {} is no longer {}", name, condition.name()),
+            );
+        }
+    }
+    let action = creature_act(game, index);
+    game.scheduler
+        .schedule(Actor::Creature(id), action.duration(speed));
+}
+
+///Use a class feature that takes an action
+pub fn use_feature(
+    game: &mut Game,
+    screen: &mut Screen,
+) {
+    let level_number = game.character.level();
+    let features: Vec<_> = game
+        .character
+        .features(&game.classes)
+        .into_iter()
+        .filter(|feature| feature.is_active())
+        .collect();
+    if features.is_empty() {
+        game.message(
+            Severity::Info,
+            Category::General,
+            "You have no features that can be used",
+        );
+        return;
+    }
+
+    let names: Vec<String> = features
+        .iter()
+        .map(|feature| {
+            format!(
+                "{} ({} left)",
+                feature.feature.to_case(Case::Title),
+                game.character.feature_uses_left(feature).unwrap_or(0)
+            )
+        })
+        .collect();
+    let original_header = screen.get_header();
+    let original_msg = screen.get_msg();
+    screen.set_header("Features");
+    let chosen = screen.draw_menu("Choose feature to use", &names, &|index| {
+        features[index].description.clone()
+    });
+    screen.set_header(&original_header);
+    screen.set_msg(&original_msg);
+
+    let feature = match chosen {
+        Some(index) => features[index].clone(),
+        None => return,
+    };
+    let name = feature.feature.to_case(Case::Title);
+    if !game.character.use_feature(&feature, game.time) {
+        game.message(
+            Severity::Warning,
+            Category::General,
+            &format!("You have no uses of {} left, rest first", name),
+        );
+        return;
+    }
+
+    match feature.feature.as_str() {
+        "second wind" => {
+            let hit_points = d(10) + level_number;
+            game.character.heal(hit_points);
+            game.message(
+                Severity::Good,
+                Category::Combat,
+                &format!(
+                    "You catch your second wind and regain {} hit points",
+                    hit_points
+                ),
+            );
+        }
+        "rage" => game.message(Severity::Good, Category::Combat, "You fly into a rage"),
+        _ => game.message(
+            Severity::Info,
+            Category::General,
+            &format!("You use {}", name),
+        ),
+    }
+    game.take_action(Action::Feature);
+}
+
+///Check if any creature on the current level has noticed the player
+fn is_hunted(game: &mut Game) -> bool {
+    game.levels
+        .level(game.position.location())
+        .creatures
+        .iter()
+        .any(|creature| creature.aware)
+}
+
+///Rest to recover, not possible while creatures are hunting the player
+pub fn rest(
+    game: &mut Game,
+    screen: &mut Screen,
+) {
+    if is_hunted(game) {
+        game.message(
+            Severity::Warning,
+            Category::General,
+            "You can not rest while something is hunting you",
+        );
+        return;
+    }
+
+    let choices = vec![
+        "Short rest, 1 hour".to_string(),
+        "Long rest, 8 hours".to_string(),
+    ];
+    let original_header = screen.get_header();
+    let original_msg = screen.get_msg();
+    screen.set_header("Rest");
+    let chosen = screen.draw_menu("Choose how long to rest", &choices, &|index| {
+        if index == 0 {
+            "Regain hit points from one hit die, and recharge features and spell \
+             slots that come back after a short rest"
+                .to_string()
+        } else {
+            "Regain all hit points, and recharge every feature and spell slot. Prepared \
+             casters can change their spells"
+                .to_string()
+        }
+    });
+    screen.set_header(&original_header);
+    screen.set_msg(&original_msg);
+
+    let (long, seconds) = match chosen {
+        Some(0) => (false, SHORT_REST_SECONDS),
+        Some(_) => (true, LONG_REST_SECONDS),
+        None => return,
+    };
+    game.resting = Some(long);
+    game.take_action(Action::Rest(seconds));
+    game.message(
+        Severity::Info,
+        Category::General,
+        if long {
+            "You settle down for a long rest"
+        } else {
+            "You settle down for a short rest"
+        },
+    );
+}
+
+///Recover once a rest has run its course undisturbed
+pub fn finish_rest(
+    game: &mut Game,
+    screen: &mut Screen,
+    long: bool,
+) {
+    if long {
+        game.character.rest(&game.classes, true);
+        game.message(
+            Severity::Good,
+            Category::General,
+            "You take a long rest and wake fully recovered",
+        );
+        game.choose_spells(screen, true);
+    } else {
+        let hit_die = game.classes.hit_die(&game.character.class) as u32;
+        let constitution = ability_modifier(game.character.abilities.constitution);
+        let hit_points = (d(hit_die) as i32 + constitution).max(1) as u32;
+        game.character.heal(hit_points);
+        game.character.rest(&game.classes, false);
+        game.message(
+            Severity::Good,
+            Category::General,
+            &format!("You take a short rest and regain {} hit points", hit_points),
+        );
+    }
+}
+
+///Wake the player from a rest once something comes for them, giving them
+///their turn straight away
+pub fn disturb_rest(game: &mut Game) {
+    if game.resting.is_none() || !is_hunted(game) {
+        return;
+    }
+    game.resting = None;
+    game.scheduler.retain(|actor| actor != Actor::Player);
+    game.scheduler.schedule(Actor::Player, 0);
+    game.message(
+        Severity::Danger,
+        Category::General,
+        "Your rest is cut short",
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::actor::creatures::Creatures;
+    use crate::items::weapons::Weapons;
+
+    fn weapon(name: &str) -> Weapon {
+        Weapons::new().weapon(name).unwrap()
+    }
+
+    ///A new game with a lone kobold on the current level
+    fn game_with_kobold(aware: bool) -> Game {
+        let mut game = Game::new();
+        game.position.level_number = 0;
+        let mut kobold = Creatures::new()
+            .spawn("kobold", Point { col: 1, row: 1 })
+            .unwrap();
+        kobold.aware = aware;
+        let level = game.levels.level(game.position.location());
+        level.creatures.clear();
+        level.creatures.push(kobold);
+        game
+    }
+
+    #[test]
+    fn natural_rolls_decide_the_hit() {
+        assert!(attack_hits(20, -10, 30));
+        assert!(!attack_hits(1, 10, 5));
+        assert!(attack_hits(10, 2, 12));
+        assert!(!attack_hits(10, 1, 12));
+    }
+
+    #[test]
+    fn rage_adds_damage_to_strength_melee_only() {
+        assert_eq!(rage_damage(2, true, false, true), 2);
+        assert_eq!(rage_damage(2, false, false, true), 0);
+        assert_eq!(rage_damage(2, true, true, false), 0);
+        assert_eq!(rage_damage(2, true, false, false), 0);
+    }
+
+    #[test]
+    fn resistance_halves_damage() {
+        assert_eq!(resisted_damage(7, "poison", true, false), 3);
+        assert_eq!(resisted_damage(7, "slashing", false, true), 3);
+        assert_eq!(resisted_damage(7, "fire", false, true), 7);
+        assert_eq!(resisted_damage(7, "slashing", false, false), 7);
+        //Both together still only halve once
+        assert_eq!(resisted_damage(8, "piercing", true, true), 4);
+    }
+
+    #[test]
+    fn killing_a_creature_removes_it_and_gives_experience() {
+        let mut game = game_with_kobold(false);
+        let experience = game.character.experience();
+        damage_creature(&mut game, 0, 1000);
+        assert!(game
+            .levels
+            .level(game.position.location())
+            .creatures
+            .is_empty());
+        assert!(game.character.experience() > experience);
+    }
+
+    #[test]
+    fn wounding_a_creature_makes_it_aware() {
+        let mut game = game_with_kobold(false);
+        damage_creature(&mut game, 0, 0);
+        let creatures = &game.levels.level(game.position.location()).creatures;
+        assert_eq!(creatures.len(), 1);
+        assert!(creatures[0].aware);
+    }
+
+    #[test]
+    fn only_aware_creatures_block_rest() {
+        assert!(!is_hunted(&mut game_with_kobold(false)));
+        assert!(is_hunted(&mut game_with_kobold(true)));
+    }
+
+    #[test]
+    fn unarmed_strike_deals_one() {
+        let dice = attack_dice(None, true);
+        assert_eq!(dice.count, 0);
+        assert_eq!(dice.roll(), 1);
+    }
+
+    #[test]
+    fn versatile_dice_only_with_both_hands() {
+        let longsword = weapon("longsword");
+        assert_eq!(
+            attack_dice(Some(&longsword), true),
+            longsword.versatile_dice().unwrap()
+        );
+        assert_eq!(
+            attack_dice(Some(&longsword), false),
+            longsword.damage_dice()
+        );
+        //Other weapons are the same either way
+        let dagger = weapon("dagger");
+        assert_eq!(attack_dice(Some(&dagger), true), dagger.damage_dice());
+    }
+
+    #[test]
+    fn finesse_uses_the_better_ability() {
+        assert!(uses_strength(true, false, false, 3, 1));
+        assert!(!uses_strength(true, false, false, 1, 3));
+        assert!(!uses_strength(true, true, false, 1, 3));
+        //A tie goes to strength
+        assert!(uses_strength(true, false, false, 2, 2));
+    }
+
+    #[test]
+    fn ranged_uses_dexterity_unless_thrown() {
+        assert!(!uses_strength(false, true, false, 3, 1));
+        assert!(uses_strength(false, true, true, 1, 3));
+        assert!(uses_strength(false, false, false, 1, 3));
+    }
+
+    #[test]
+    fn off_hand_adds_only_a_penalty() {
+        assert_eq!(damage_modifier(3, false), 3);
+        assert_eq!(damage_modifier(3, true), 0);
+        assert_eq!(damage_modifier(-1, true), -1);
+    }
+
+    #[test]
+    fn heavy_weapons_are_unwieldy_only_for_small_folk() {
+        let greataxe = weapon("greataxe");
+        assert!(greataxe.heavy());
+        assert!(unwieldy(Some(&greataxe), true));
+        assert!(!unwieldy(Some(&greataxe), false));
+        assert!(!unwieldy(Some(&weapon("dagger")), true));
+        assert!(!unwieldy(None, true));
+    }
+}
@@ -0,0 +1,233 @@
+use crate::game::{direction, Game};
+use crate::levels::{ExportFormat, Level};
+use crate::screen::Screen;
+use crate::utils::*;
+use convert_case::{Case, Casing};
+
+///Show the whole explored level scaled down to fit on screen
+pub fn overview(
+    game: &mut Game,
+    screen: &mut Screen,
+) {
+    let original_header = screen.get_header();
+    let original_msg = screen.get_msg();
+    let original_sidebar = screen.get_sidebar();
+    let location = game.position.location();
+    let player_p = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+
+    //Use the full width for the overview
+    screen.set_sidebar(Vec::new());
+    let level = game.levels.level(location);
+    let overview_vec = level.overview_vec(screen.msg_columns(), screen.msg_rows(), &player_p);
+    let block_columns = level.width().div_ceil(overview_vec[0].len());
+    let block_rows = level.height().div_ceil(overview_vec.len());
+
+    screen.set_header(&format!(
+        "Map Overview - {} L{} - {}x{} tiles per symbol",
+        game.levels
+            .branch_name(location.branch)
+            .to_case(Case::Title),
+        location.level_number,
+        block_columns,
+        block_rows
+    ));
+    let (center_x, center_y) = (overview_vec[0].len() / 2, overview_vec.len() / 2);
+    screen.set_map_view(overview_vec, center_x as i32, center_y as i32, &[]);
+    if screen.draw_enter_char("Browse full map: M Return: any other key") == 'M' {
+        screen.set_sidebar(original_sidebar.clone());
+        browse(game, screen);
+    }
+
+    screen.set_header(&original_header);
+    screen.set_msg(&original_msg);
+    screen.set_sidebar(original_sidebar);
+}
+
+///Scroll around the full size map of the level with a cursor
+pub fn browse(
+    game: &mut Game,
+    screen: &mut Screen,
+) {
+    map_cursor(game, screen, "Map", false, None, &|_, _| Vec::new());
+}
+
+///Pick a position on the full size map of the level with a cursor
+pub fn select_position(
+    game: &mut Game,
+    screen: &mut Screen,
+    title: &str,
+) -> Option<Point> {
+    map_cursor(game, screen, title, true, None, &|_, _| Vec::new())
+}
+
+///Pick a target on the map, starting on the nearest creature in view
+///
+///The area the target would cover is shown around the cursor
+pub fn select_target(
+    game: &mut Game,
+    screen: &mut Screen,
+    title: &str,
+    area: &dyn Fn(&Level, Point) -> Vec<Point>,
+) -> Option<Point> {
+    let player_p = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+    let level = game.levels.level(game.position.location());
+    let nearest = level
+        .creatures
+        .iter()
+        .filter(|creature| level.is_visible(creature.position))
+        .map(|creature| creature.position)
+        .min_by_key(|p| distance(p, &player_p));
+    map_cursor(game, screen, title, true, nearest, area)
+}
+
+///Move a cursor around the full size map of the level
+///
+///When selecting, enter or . picks the position under the cursor. Any area
+///given for the cursor position is marked with *
+fn map_cursor(
+    game: &mut Game,
+    screen: &mut Screen,
+    title: &str,
+    select: bool,
+    start: Option<Point>,
+    area: &dyn Fn(&Level, Point) -> Vec<Point>,
+) -> Option<Point> {
+    let original_header = screen.get_header();
+    let original_msg = screen.get_msg();
+    let location = game.position.location();
+    let player_p = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+
+    let view_distance = game.view_distance();
+    let map_vec = game.levels.map_vec(location, &player_p, view_distance);
+    //Stairs up, then stairs down
+    let stairs: [Vec<Point>; 2] = [true, false].map(|up| {
+        game.levels
+            .level(location)
+            .remembered_stairs()
+            .iter()
+            .filter(|stair| stair.up == up)
+            .map(|stair| stair.position)
+            .collect()
+    });
+    let width = game.levels.level(location).width() as i32;
+    let height = game.levels.level(location).height() as i32;
+    let mut stair_indexes = [0, 0];
+    let mut cursor = start.unwrap_or(player_p);
+    let mut selected: Option<Point> = None;
+
+    screen.set_header(&format!(
+        "{} - {} L{}",
+        title,
+        game.levels
+            .branch_name(location.branch)
+            .to_case(Case::Title),
+        location.level_number
+    ));
+    loop {
+        //The first marker at a position is the one drawn
+        let mut markers: Vec<(Point, char)> = vec![(cursor, 'X'), (player_p, '@')];
+        markers.extend(
+            area(game.levels.level(location), cursor)
+                .into_iter()
+                .map(|p| (p, '*')),
+        );
+        screen.set_map_view(
+            map_vec.clone(),
+            cursor.col as i32,
+            cursor.row as i32,
+            &markers,
+        );
+        let key = screen.draw_enter_char(&format!(
+            "{} | Move: directions, shift x10 Stairs: <> {}Back: q",
+            game.levels.describe(location, cursor),
+            if select { "Select: . " } else { "" }
+        ));
+        match key {
+            'q' | '\x1b' => break,
+            '\r' | '\n' | '.' => {
+                if select {
+                    selected = Some(cursor);
+                }
+                break;
+            }
+            '@' => cursor = player_p,
+            '<' | '>' => {
+                let down = (key == '>') as usize;
+                if !stairs[down].is_empty() {
+                    cursor = stairs[down][stair_indexes[down] % stairs[down].len()];
+                    stair_indexes[down] += 1;
+                }
+            }
+            _ => {
+                if let Some((dx, dy)) = direction(key.to_ascii_lowercase()) {
+                    let distance = if key.is_ascii_uppercase() { 10 } else { 1 };
+                    cursor = Point {
+                        col: (cursor.col as i32 + dx * distance).clamp(0, width - 1) as usize,
+                        row: (cursor.row as i32 + dy * distance).clamp(0, height - 1) as usize,
+                    };
+                }
+            }
+        }
+    }
+
+    screen.set_header(&original_header);
+    screen.set_msg(&original_msg);
+    selected
+}
+
+///Export the remembered map of the current level to a file
+pub fn export(
+    game: &mut Game,
+    screen: &mut Screen,
+) {
+    let original_header = screen.get_header();
+    let original_msg = screen.get_msg();
+    let location = game.position.location();
+    let formats = [ExportFormat::Text, ExportFormat::Html, ExportFormat::Png];
+
+    let names = vec!["Text".to_string(), "HTML".to_string(), "PNG".to_string()];
+
+    screen.set_header("Export Map");
+    let chosen = screen.draw_menu("Choose format", &names, &|index| {
+        match formats[index] {
+            ExportFormat::Text => "Plain characters, as the map is drawn on screen",
+            ExportFormat::Html => "A web page with the map in color",
+            ExportFormat::Png => "An image with a few pixels for every tile",
+        }
+        .to_string()
+    });
+    let format = match chosen {
+        Some(index) => formats[index],
+        None => {
+            screen.set_header(&original_header);
+            screen.set_msg(&original_msg);
+            return;
+        }
+    };
+
+    let file = format!(
+        "{}_{}_L{}.{}",
+        game.character.name,
+        game.levels.branch_name(location.branch),
+        location.level_number,
+        format.extension()
+    )
+    .replace(' ', "_");
+    match game.levels.export(location, &file, false) {
+        Ok(()) => screen.set_msg(&format!("Exported map to '{}'", file)),
+        Err(e) => screen.set_msg(&format!("Unable to export map: {}", e)),
+    }
+    screen.draw_display();
+
+    screen.set_header(&original_header);
+    screen.set_msg(&original_msg);
+}
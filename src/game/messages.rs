@@ -0,0 +1,168 @@
+use crate::game::{direction, Game};
+use crate::screen::{Screen, COLUMN_WIDTH};
+use crate::utils::*;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+///Most messages kept in the history, older ones are dropped
+const HISTORY_LENGTH: usize = 500;
+
+///Messages shown in the log panel under the map
+pub const LOG_PANEL_ROWS: usize = 3;
+
+///How important a message is
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Severity {
+    Info,
+    Good,
+    Warning,
+    Danger,
+}
+impl Severity {
+    ///Short tag shown in front of a message in the log panel
+    fn tag(&self) -> &str {
+        match self {
+            Severity::Info => " ",
+            Severity::Good => "+",
+            Severity::Warning => "!",
+            Severity::Danger => "*",
+        }
+    }
+}
+
+///What a message is about
+#[derive(Clone, Copy, Serialize, Deserialize, Debug, PartialEq)]
+pub enum Category {
+    General,
+    Movement,
+    Exploration,
+    Combat,
+    System,
+}
+
+///A single logged event
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct Message {
+    time: u32,
+    severity: Severity,
+    category: Category,
+    text: String,
+}
+
+///Recent game events, the history is kept in the save
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct MessageLog {
+    messages: VecDeque<Message>,
+}
+
+impl MessageLog {
+    ///Add a message to the log
+    pub fn add(
+        &mut self,
+        time: u32,
+        severity: Severity,
+        category: Category,
+        text: &str,
+    ) {
+        self.messages.push_back(Message {
+            time,
+            severity,
+            category,
+            text: text.to_string(),
+        });
+        while self.messages.len() > HISTORY_LENGTH {
+            self.messages.pop_front();
+        }
+    }
+
+    ///Lines for the log panel, oldest first
+    pub fn panel_lines(
+        &self,
+        rows: usize,
+    ) -> Vec<String> {
+        let mut lines: Vec<String> = self
+            .messages
+            .iter()
+            .rev()
+            .take(rows)
+            .rev()
+            .map(|message| {
+                format!("{} {}", message.severity.tag(), message.text)
+                    .chars()
+                    .take(COLUMN_WIDTH)
+                    .collect()
+            })
+            .collect();
+        while lines.len() < rows {
+            lines.insert(0, "".to_string());
+        }
+        lines
+    }
+
+    ///Lines for the history screen, oldest first
+    fn history_lines(
+        &self,
+        width: usize,
+    ) -> Vec<String> {
+        let mut lines: Vec<String> = Vec::new();
+        for message in self.messages.iter() {
+            let prefix = format!(
+                "{} [{:?}/{:?}] ",
+                CompoundTime::new(message.time),
+                message.category,
+                message.severity
+            );
+            let text = textwrap::fill(
+                &message.text,
+                textwrap::Options::new(width)
+                    .initial_indent(&prefix)
+                    .subsequent_indent("    "),
+            );
+            lines.extend(text.split('\n').map(|s| s.to_string()));
+        }
+        lines
+    }
+}
+
+///Scroll through every message in the history
+pub fn history(
+    game: &Game,
+    screen: &mut Screen,
+) {
+    let original_header = screen.get_header();
+    let original_msg = screen.get_msg();
+    let original_log = screen.get_log();
+
+    screen.set_log(Vec::new());
+    let lines = game.log.history_lines(screen.msg_columns());
+    let rows = screen.msg_rows();
+    let last_top = lines.len().saturating_sub(rows);
+    //Start at the most recent messages
+    let mut top = last_top;
+
+    screen.set_header(&format!(
+        "Message History - {} messages",
+        game.log.messages.len()
+    ));
+    loop {
+        if lines.is_empty() {
+            screen.set_msg("No messages yet");
+        } else {
+            screen.set_msg(&lines[top..(top + rows).min(lines.len())].join("\n"));
+        }
+        let key = screen.draw_enter_char("Scroll: ws/kj, shift for a page Back: q");
+        match key {
+            'q' | '\x1b' => break,
+            _ => {
+                if let Some((_, dy)) = direction(key.to_ascii_lowercase()) {
+                    let distance = if key.is_ascii_uppercase() { rows } else { 1 } as i32;
+                    top = (top as i32 + dy * distance).clamp(0, last_top as i32) as usize;
+                }
+            }
+        }
+    }
+
+    screen.set_header(&original_header);
+    screen.set_msg(&original_msg);
+    screen.set_log(original_log);
+}
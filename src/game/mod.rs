@@ -1,14 +1,48 @@
+mod ai;
+mod casting;
+mod combat;
+mod map;
+mod messages;
+mod ranged;
+mod scheduler;
 mod step;
+mod travel;
 
-use crate::actor::player::Character;
-use crate::levels::Levels;
+use crate::actor::creatures::Creatures;
+use crate::actor::energy::{Action, TICKS_PER_SECOND};
+use crate::actor::player::backgrounds::Backgrounds;
+use crate::actor::player::classes::{Classes, SpellPreparation};
+use crate::actor::player::races::Races;
+use crate::actor::player::{choose_spells, level_up, Character, Encumbrance};
+use crate::actor::spells::{Spells, FEET_PER_TILE};
+use crate::levels::{Levels, Location};
 use crate::screen::Screen;
 use crate::game::step::*;
+use crate::game::messages::{Category, MessageLog, Severity, LOG_PANEL_ROWS};
+use crate::game::scheduler::{Actor, Scheduler};
+use crate::game::travel::Activity;
+use crate::items::ammunition::Ammunitions;
+use crate::items::weapons::Weapons;
+use crate::items::Item;
 use crate::utils::*;
+use convert_case::{Case, Casing};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use sm::sm;
 use std::fs;
 
+///Experience for each level of depth the first time a level is reached
+const DESCEND_EXPERIENCE: u32 = 50;
+
+///Creatures placed on a level the first time it is reached, plus one per depth
+const LEVEL_CREATURES: usize = 4;
+
+///Closest a creature is placed to the player on arrival
+const CREATURE_START_DISTANCE: usize = 10;
+
+///Feet lit by the torch the player carries
+const TORCH_RANGE: u32 = 100;
+
 sm! {
     GameState {
         InitialStates { Idle }
@@ -25,7 +59,9 @@ sm! {
 use crate::game::GameState::{Variant::*, *};
 
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
 struct Position {
+    branch: usize,
     level_number: i32,
     x: i32,
     y: i32,
@@ -34,6 +70,7 @@ struct Position {
 impl Default for Position {
     fn default() -> Self {
         Position {
+            branch: 0,
             level_number: -1,
             x: 0,
             y: 0,
@@ -41,6 +78,31 @@ impl Default for Position {
     }
 }
 
+impl Position {
+    ///Branch and level the position is on
+    fn location(&self) -> Location {
+        Location {
+            branch: self.branch,
+            level_number: self.level_number as usize,
+        }
+    }
+}
+
+///Direction a movement key points in, as column and row offsets
+fn direction(key: char) -> Option<(i32, i32)> {
+    match key {
+        'y' => Some((-1, -1)),
+        'w' | 'k' => Some((0, -1)),
+        'u' => Some((1, -1)),
+        'a' | 'h' => Some((-1, 0)),
+        'd' | 'l' => Some((1, 0)),
+        'b' => Some((-1, 1)),
+        's' | 'j' => Some((0, 1)),
+        'n' => Some((1, 1)),
+        _ => None,
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(default)]
 pub struct Game {
@@ -48,6 +110,45 @@ pub struct Game {
     pub levels: Levels,
     position: Position,
     time: u32,
+    log: MessageLog,
+    //Levels already reached, experience is only given the first time
+    visited: Vec<Location>,
+    #[serde(skip)]
+    activity: Option<Activity>,
+    #[serde(skip)]
+    scheduler: Scheduler,
+    //What the player spent the current turn on, None until something is done
+    #[serde(skip)]
+    player_action: Option<Action>,
+    //Round the player last took a turn in
+    #[serde(skip)]
+    round: u32,
+    //A weapon with the loading property was fired this round
+    #[serde(skip)]
+    loading_fired: bool,
+    //The extra attack with a light weapon in the off hand was made this round
+    #[serde(skip)]
+    off_hand_attacked: bool,
+    //Sneak attack damage was dealt this round
+    #[serde(skip)]
+    sneak_attacked: bool,
+    //Resting until the player's next turn, true for a long rest
+    #[serde(skip)]
+    resting: Option<bool>,
+    #[serde(skip)]
+    pub races: Races,
+    #[serde(skip)]
+    pub classes: Classes,
+    #[serde(skip)]
+    pub backgrounds: Backgrounds,
+    #[serde(skip)]
+    pub weapons: Weapons,
+    #[serde(skip)]
+    pub creatures: Creatures,
+    #[serde(skip)]
+    pub spells: Spells,
+    #[serde(skip)]
+    pub ammunition: Ammunitions,
 }
 
 impl Game {
@@ -57,6 +158,208 @@ impl Game {
         }
     }
 
+    ///Add a message to the log
+    fn message(
+        &mut self,
+        severity: Severity,
+        category: Category,
+        text: &str,
+    ) {
+        self.log.add(self.time, severity, category, text);
+    }
+
+    ///Spend the player's turn on an action
+    fn take_action(
+        &mut self,
+        action: Action,
+    ) {
+        self.player_action = Some(action);
+    }
+
+    ///Take the actor whose turn is next, bringing the game time up to it
+    fn next_actor(&mut self) -> Option<Actor> {
+        let actor = self.scheduler.next();
+        self.time = (self.scheduler.now() / TICKS_PER_SECOND) as u32;
+        actor
+    }
+
+    ///Queue up the creatures on the current level, spread out so they do not
+    ///all move at once
+    ///
+    ///Creatures queued from a level the player has left are dropped, leaving
+    ///them frozen until the player is back
+    fn schedule_creatures(&mut self) {
+        self.scheduler.retain(|actor| actor == Actor::Player);
+        let level = self.levels.level(self.position.location());
+        level.assign_creature_ids();
+        let mut rng = rand::thread_rng();
+        for creature in level.creatures.iter() {
+            let delay = rng.gen_range(0..Action::Move.duration(creature.current_speed()).max(1));
+            self.scheduler.schedule(Actor::Creature(creature.id), delay);
+        }
+    }
+
+    ///Give the character experience, saying why
+    fn gain_experience(
+        &mut self,
+        experience: u32,
+        reason: &str,
+    ) {
+        self.character.gain_experience(experience);
+        self.message(
+            Severity::Good,
+            Category::General,
+            &format!("You gain {} experience for {}", experience, reason),
+        );
+    }
+
+    ///Give experience and place creatures when reaching a level for the first time
+    fn visit_level(&mut self) {
+        let location = self.position.location();
+        if self.visited.contains(&location) {
+            return;
+        }
+        self.visited.push(location);
+
+        let depth = self.levels.depth(location);
+        let player = Point {
+            col: self.position.x as usize,
+            row: self.position.y as usize,
+        };
+        for _ in 0..LEVEL_CREATURES + depth {
+            let creatures = &self.creatures;
+            let level = self.levels.level(location);
+            if let Some(creature) = level
+                .random_empty_floor(player, CREATURE_START_DISTANCE)
+                .and_then(|p| creatures.spawn_for_depth(depth, p))
+            {
+                level.creatures.push(creature);
+            }
+        }
+
+        let depth = depth as u32;
+        if depth > 0 {
+            self.gain_experience(
+                DESCEND_EXPERIENCE * depth,
+                &format!("reaching depth {}", depth),
+            );
+        }
+    }
+
+    ///Go through the level up screens for each level the character has the
+    ///experience for
+    fn level_up(
+        &mut self,
+        screen: &mut Screen,
+    ) {
+        let original_msg = screen.get_msg();
+
+        while self.character.can_level_up() {
+            level_up(screen, &mut self.character, &self.races, &self.classes);
+            let level = self.character.level();
+            self.message(
+                Severity::Good,
+                Category::General,
+                &format!("You are now level {}", level),
+            );
+            self.choose_spells(screen, true);
+        }
+
+        screen.set_msg(&original_msg);
+    }
+
+    ///Pick spells if the class casts them and there is anything to pick
+    ///
+    ///Prepared casters may also swap spells when replacing is allowed
+    fn choose_spells(
+        &mut self,
+        screen: &mut Screen,
+        replace: bool,
+    ) {
+        let prepared = matches!(
+            self.character.spellcasting(&self.classes),
+            Some(spellcasting) if spellcasting.preparation == SpellPreparation::Prepared
+        );
+        if (replace && prepared)
+            || self
+                .character
+                .can_choose_spells(&self.classes, &self.spells)
+        {
+            choose_spells(
+                screen,
+                &mut self.character,
+                &self.classes,
+                &self.spells,
+                replace && prepared,
+            );
+        }
+    }
+
+    ///Tiles the player can see, torchlight with darkvision reaching on past
+    ///it, less when blinded
+    fn view_distance(&self) -> usize {
+        let darkvision = self.races.effects(&self.character.race).darkvision;
+        let distance = ((TORCH_RANGE + darkvision) / FEET_PER_TILE) as usize;
+        match self.character.conditions.sight_limit() {
+            Some(limit) => limit.min(distance),
+            None => distance,
+        }
+    }
+
+    ///Lines of the status sidebar
+    fn status(&self) -> Vec<String> {
+        let time = CompoundTime::new(self.time)
+            .to_string()
+            .replace(" Time:", "\nTime:");
+        let mut status: Vec<String> = vec![
+            format!(
+                "HP: {}/{}",
+                self.character.hit_points(&self.races, &self.classes),
+                self.character.max_hit_points(&self.races, &self.classes)
+            ),
+            format!("AC: {}", self.character.armor_class()),
+            format!("Speed: {} ft", self.character.movement_speed(&self.races)),
+            "".to_string(),
+            "".to_string(),
+            format!("Level: {}", self.character.level()),
+            format!("XP: {}", self.character.experience()),
+            "".to_string(),
+            format!(
+                "{} L{}",
+                self.levels
+                    .branch_name(self.position.branch)
+                    .to_case(Case::Title),
+                self.position.level_number
+            ),
+            "".to_string(),
+        ];
+        let slots = self.character.spell_slots_summary(&self.classes);
+        if !slots.is_empty() {
+            status.insert(3, format!("Slots: {}", slots.join(" ")));
+        }
+        if self.character.is_feature_active("rage", self.time) {
+            status.insert(3, "Raging".to_string());
+        }
+        let encumbrance = self.character.encumbrance();
+        if encumbrance != Encumbrance::Unencumbered {
+            status.insert(3, encumbrance.name().to_case(Case::Title));
+        }
+        for condition in self.character.conditions.iter() {
+            status.insert(3, condition.name().to_case(Case::Title));
+        }
+        status.extend(time.lines().map(|line| line.to_string()));
+        status.iter().map(|line| format!(" {}", line)).collect()
+    }
+
+    ///Refresh the status sidebar and message log panel
+    fn update_panels(
+        &self,
+        screen: &mut Screen,
+    ) {
+        screen.set_sidebar(self.status());
+        screen.set_log(self.log.panel_lines(LOG_PANEL_ROWS));
+    }
+
     pub fn save(
         &self,
         file: &str,
@@ -65,12 +368,24 @@ impl Game {
         fs::write(file, game_str).expect("Unable to save file");
     }
 
+    ///Load a saved game
+    ///
+    ///Levels that had to be generated anew, as for saves from before branches,
+    ///put the player back at the entrance
     pub fn load(
         &mut self,
         file: &str,
-    ) {
-        let game_str = fs::read_to_string(&file).expect("Unable to open file");
-        *self = serde_json::from_str(&game_str).unwrap();
+    ) -> Result<(), String> {
+        let game_str = fs::read_to_string(file).map_err(|e| e.to_string())?;
+        *self = serde_json::from_str(&game_str).map_err(|e| e.to_string())?;
+
+        let level = self.levels.level(self.position.location());
+        if !level.is_open(self.position.x, self.position.y) {
+            let entrance = level.entrance();
+            self.position.x = entrance.col as i32;
+            self.position.y = entrance.row as i32;
+        }
+        Ok(())
     }
 
     pub fn run(
@@ -87,33 +402,51 @@ impl Game {
                     screen.set_msg("Entering into dungeon...");
                     screen.draw_display();
 
+                    //New casters, and saves from before spells, start by choosing spells
+                    self.choose_spells(screen, false);
+
                     m.transition(ChooseNavigate).as_enum()
                 }
                 NavigateByChooseNavigate(m) => {
                     //Generate level 0, set position
                     if self.position.level_number == -1 {
+                        self.position.branch = 0;
                         self.position.level_number = 0;
-                        self.levels.level(self.position.level_number as usize);
-                        let position_p = self
-                            .levels
-                            .level_start_position(self.position.level_number as usize);
+                        let position_p = self.levels.level_start_position(self.position.location());
                         self.position.x = position_p.col as i32;
                         self.position.y = position_p.row as i32;
+                        self.message(
+                            Severity::Info,
+                            Category::General,
+                            &format!("Welcome {}, press ? for help", self.character.name),
+                        );
+                        self.visit_level();
+
+                        //A bundle of ammunition for each weapon that needs it
+                        for weapon in self.character.weapons.clone().iter() {
+                            if let Some(bundle) = self.ammunition.bundle_for(&weapon.name()) {
+                                if self.character.ammunition_left(&weapon.name()) == 0 {
+                                    self.character.pick_up(Item::Ammunition(bundle));
+                                }
+                            }
+                        }
                     }
 
                     screen.set_header(&format!(
-                        "{} - {} - L{}",
+                        "{} - {} {}",
                         self.character.name,
-                        CompoundTime::new(self.time).to_string(),
-                        self.position.level_number
+                        self.character.race.to_case(Case::Title),
+                        self.character.class.to_case(Case::Title)
                     ));
+                    self.update_panels(screen);
                     screen.set_map(
                         self.levels.map_vec(
-                            self.position.level_number as usize,
+                            self.position.location(),
                             &Point {
                                 col: self.position.x as usize,
                                 row: self.position.y as usize,
                             },
+                            self.view_distance(),
                         ),
                         self.position.x,
                         self.position.y,
@@ -122,8 +455,6 @@ impl Game {
                     //Step through actor's actions
                     let quit = step(self,screen);
 
-                    self.time += 1;
-
                     if quit {
                         m.transition(Done).as_enum()
                     } else {
@@ -132,6 +463,8 @@ impl Game {
                 }
                 FinishedByDone(_) => {
                     screen.set_header(&original_header);
+                    screen.set_log(Vec::new());
+                    screen.set_sidebar(Vec::new());
                     break;
                 }
             }
@@ -0,0 +1,173 @@
+use crate::actor::energy::Action;
+use crate::actor::spells::FEET_PER_TILE;
+use crate::game::combat::weapon_attack;
+use crate::game::map::select_target;
+use crate::game::messages::{Category, Severity};
+use crate::game::Game;
+use crate::items::weapons::Weapon;
+use crate::items::Item;
+use crate::levels::Level;
+use crate::screen::Screen;
+use crate::utils::*;
+use convert_case::{Case, Casing};
+
+///Chance in 2 of finding a fired piece of ammunition again
+const RECOVER_CHANCE: u32 = 1;
+
+///Fire a ranged weapon or throw a thrown one at a chosen target
+pub fn fire(
+    game: &mut Game,
+    screen: &mut Screen,
+) {
+    let weapons: Vec<Weapon> = game
+        .character
+        .weapons
+        .iter()
+        .filter(|weapon| (weapon.class() == "ranged" || weapon.thrown()) && !weapon.special())
+        .cloned()
+        .collect();
+    let weapon = match weapons.len() {
+        0 => {
+            game.message(
+                Severity::Info,
+                Category::Combat,
+                "You have nothing to fire or throw",
+            );
+            return;
+        }
+        1 => weapons[0].clone(),
+        _ => {
+            let names: Vec<String> = weapons
+                .iter()
+                .map(|weapon| weapon.name().to_case(Case::Title))
+                .collect();
+            let original_header = screen.get_header();
+            let original_msg = screen.get_msg();
+            screen.set_header("Fire or Throw");
+            let chosen =
+                screen.draw_menu("Choose weapon", &names, &|index| weapons[index].details());
+            screen.set_header(&original_header);
+            screen.set_msg(&original_msg);
+            match chosen {
+                Some(index) => weapons[index].clone(),
+                None => return,
+            }
+        }
+    };
+    let name = weapon.name();
+
+    if weapon.ammunition() && game.character.ammunition_left(&name) == 0 {
+        game.message(
+            Severity::Warning,
+            Category::Combat,
+            &format!("You have no ammunition for your {}", name),
+        );
+        return;
+    }
+    //A loading weapon only fires once a turn, however many actions there are
+    if weapon.loading() && game.loading_fired {
+        game.message(
+            Severity::Warning,
+            Category::Combat,
+            &format!("You are still loading your {}", name),
+        );
+        return;
+    }
+
+    let player = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+    let path = |level: &Level, cursor: Point| level.projectile_path(player, cursor);
+    let target = match select_target(
+        game,
+        screen,
+        &format!(
+            "{} {}",
+            if weapon.ammunition() { "Fire" } else { "Throw" },
+            name.to_case(Case::Title)
+        ),
+        &path,
+    ) {
+        Some(target) => target,
+        None => return,
+    };
+
+    let range_normal = (weapon.range_normal() / FEET_PER_TILE) as usize;
+    let range_max = (weapon.range_max() / FEET_PER_TILE) as usize;
+    let level = game.levels.level(game.position.location());
+    if target == player {
+        return;
+    }
+    if distance(&player, &target) > range_max {
+        game.message(
+            Severity::Warning,
+            Category::Combat,
+            &format!(
+                "That is out of range, your {} reaches {} ft",
+                name,
+                weapon.range_max()
+            ),
+        );
+        return;
+    }
+    if !level.is_visible(target) {
+        game.message(Severity::Warning, Category::Combat, "You can not see there");
+        return;
+    }
+
+    //Loose the shot, it flies until something is in the way
+    let path = level.projectile_path(player, target);
+    let landing = path.last().copied().unwrap_or(player);
+    let hit_index = level.creature_at(landing);
+    //Shooting with an enemy close by is as hard as at long range
+    let threatened = level
+        .creatures
+        .iter()
+        .any(|creature| creature.aware && distance(&creature.position, &player) == 1);
+    let disadvantage = distance(&player, &target) > range_normal || threatened;
+
+    let item = if weapon.ammunition() {
+        game.character.take_ammunition(&name).map(Item::Ammunition)
+    } else {
+        //Throw a spare from the pack if there is one, keeping hold of the one in hand
+        game.character.take_weapon(&name).map(Item::Weapon)
+    };
+    game.take_action(Action::Attack);
+    if weapon.loading() {
+        game.loading_fired = true;
+    }
+
+    match hit_index {
+        Some(index) => {
+            weapon_attack(game, index, Some(&weapon), true, disadvantage, false);
+        }
+        None => game.message(
+            Severity::Info,
+            Category::Combat,
+            &format!(
+                "Your {} {}",
+                if weapon.ammunition() { "shot" } else { &name },
+                if landing == target {
+                    "hits nothing"
+                } else {
+                    "hits the wall"
+                }
+            ),
+        ),
+    }
+
+    //Thrown weapons can always be picked up again, about half of the
+    //ammunition fired can be found where it landed
+    if let Some(item) = item {
+        let recovered = match item {
+            Item::Weapon(_) => true,
+            Item::Ammunition(_) => d(2) <= RECOVER_CHANCE,
+        };
+        if recovered {
+            game.levels
+                .level(game.position.location())
+                .drop_item(landing, item);
+        }
+    }
+}
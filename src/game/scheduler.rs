@@ -0,0 +1,165 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+///Someone who takes turns, creatures by their id on the current level
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Actor {
+    Player,
+    Creature(u32),
+}
+
+///Actors on the current level, ordered by the tick of their next action
+///
+///Ties go to whoever was scheduled first. Creatures on other levels are left
+///out, so they stay frozen until the player returns
+#[derive(Debug, Default)]
+pub struct Scheduler {
+    queue: BinaryHeap<Reverse<(u64, u64, Actor)>>,
+    //Tick of the turn being taken now
+    now: u64,
+    //Counts up as actors are scheduled, to break ties
+    sequence: u64,
+}
+
+impl Scheduler {
+    pub fn now(&self) -> u64 {
+        self.now
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+
+    ///Start the clock at a tick, forgetting everyone queued
+    pub fn start(
+        &mut self,
+        now: u64,
+    ) {
+        self.queue.clear();
+        self.now = now;
+    }
+
+    ///Give an actor its next turn a number of ticks from now
+    pub fn schedule(
+        &mut self,
+        actor: Actor,
+        delay: u64,
+    ) {
+        self.sequence += 1;
+        self.queue
+            .push(Reverse((self.now + delay, self.sequence, actor)));
+    }
+
+    ///The actor whose turn is next, left in the queue
+    pub fn peek(&self) -> Option<Actor> {
+        self.queue.peek().map(|Reverse((_, _, actor))| *actor)
+    }
+
+    ///Take the actor whose turn is next, moving the clock up to it
+    pub fn next(&mut self) -> Option<Actor> {
+        let Reverse((tick, _, actor)) = self.queue.pop()?;
+        self.now = self.now.max(tick);
+        Some(actor)
+    }
+
+    ///Keep only the actors a check passes
+    pub fn retain(
+        &mut self,
+        keep: impl Fn(Actor) -> bool,
+    ) {
+        let queue = std::mem::take(&mut self.queue);
+        self.queue = queue
+            .into_iter()
+            .filter(|Reverse((_, _, actor))| keep(*actor))
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn soonest_turn_comes_first() {
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(Actor::Creature(1), 30);
+        scheduler.schedule(Actor::Player, 10);
+        scheduler.schedule(Actor::Creature(2), 20);
+        assert_eq!(scheduler.next(), Some(Actor::Player));
+        assert_eq!(scheduler.next(), Some(Actor::Creature(2)));
+        assert_eq!(scheduler.next(), Some(Actor::Creature(1)));
+        assert_eq!(scheduler.next(), None);
+    }
+
+    #[test]
+    fn ties_go_to_whoever_was_scheduled_first() {
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(Actor::Creature(2), 10);
+        scheduler.schedule(Actor::Player, 10);
+        scheduler.schedule(Actor::Creature(1), 10);
+        assert_eq!(scheduler.next(), Some(Actor::Creature(2)));
+        assert_eq!(scheduler.next(), Some(Actor::Player));
+        assert_eq!(scheduler.next(), Some(Actor::Creature(1)));
+    }
+
+    #[test]
+    fn clock_moves_up_to_each_turn() {
+        let mut scheduler = Scheduler::default();
+        scheduler.start(100);
+        scheduler.schedule(Actor::Player, 60);
+        assert_eq!(scheduler.peek(), Some(Actor::Player));
+        assert_eq!(scheduler.now(), 100);
+        scheduler.next();
+        assert_eq!(scheduler.now(), 160);
+        //Delays count from the turn being taken
+        scheduler.schedule(Actor::Creature(1), 5);
+        scheduler.next();
+        assert_eq!(scheduler.now(), 165);
+    }
+
+    #[test]
+    fn faster_actor_gets_more_turns() {
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(Actor::Player, 0);
+        scheduler.schedule(Actor::Creature(1), 0);
+        let mut player_turns = 0;
+        let mut creature_turns = 0;
+        loop {
+            let actor = scheduler.next().unwrap();
+            if scheduler.now() >= 600 {
+                break;
+            }
+            match actor {
+                Actor::Player => {
+                    player_turns += 1;
+                    scheduler.schedule(Actor::Player, 30);
+                }
+                actor => {
+                    creature_turns += 1;
+                    scheduler.schedule(actor, 60);
+                }
+            }
+        }
+        assert_eq!(player_turns, 2 * creature_turns);
+    }
+
+    #[test]
+    fn retain_drops_actors_and_keeps_order() {
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(Actor::Creature(1), 10);
+        scheduler.schedule(Actor::Player, 20);
+        scheduler.schedule(Actor::Creature(2), 30);
+        scheduler.retain(|actor| actor != Actor::Creature(1));
+        assert_eq!(scheduler.next(), Some(Actor::Player));
+        assert_eq!(scheduler.next(), Some(Actor::Creature(2)));
+        assert!(scheduler.is_empty());
+    }
+
+    #[test]
+    fn start_forgets_everyone() {
+        let mut scheduler = Scheduler::default();
+        scheduler.schedule(Actor::Player, 10);
+        scheduler.start(0);
+        assert!(scheduler.is_empty());
+    }
+}
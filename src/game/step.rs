@@ -1,275 +1,362 @@
-use crate::game::Game;
+use crate::actor::conditions::Condition;
+use crate::actor::energy::{Action, ROUND_SECONDS, TICKS_PER_SECOND};
+use crate::game::casting::*;
+use crate::game::combat::*;
+use crate::game::map::*;
+use crate::game::messages::*;
+use crate::game::ranged::*;
+use crate::game::scheduler::Actor;
+use crate::game::travel::*;
+use crate::game::{direction, Game};
 use crate::screen::Screen;
 use crate::utils::*;
+use convert_case::{Case, Casing};
+
+///Every command available while exploring
+const COMMANDS: &str = "\
+Move:                      wasd or ykuhbjnl
+Run:                       shift + move
+Attack:                    move into a creature
+Attack at reach:           t
+Wield weapons:             e
+Interact (stairs):         <space>
+Use class feature:         F
+Cast a spell:              Z
+Fire or throw a weapon:    f
+Rest:                      R
+Travel to stairs up/down:  < >
+Travel to a position:      _
+Explore:                   o
+Character sheet:           c
+Map overview:              m
+Browse full map:           M
+Export map:                X
+Message history:           P
+Wait:                      .
+Refresh screen:            r
+Quit:                      q";
+
+///Show every command
+fn help(screen: &mut Screen) {
+    let original_header = screen.get_header();
+    let original_msg = screen.get_msg();
+
+    screen.set_header("Commands");
+    screen.set_msg(COMMANDS);
+    screen.draw_display();
+
+    screen.set_header(&original_header);
+    screen.set_msg(&original_msg);
+}
+
+///Show the character sheet
+fn character_sheet(
+    game: &Game,
+    screen: &mut Screen,
+) {
+    let original_header = screen.get_header();
+    let original_msg = screen.get_msg();
+
+    screen.set_header("Character Sheet");
+    screen.set_msg(
+        &game
+            .character
+            .details(&game.races, &game.classes, &game.spells),
+    );
+    screen.draw_display();
+
+    screen.set_header(&original_header);
+    screen.set_msg(&original_msg);
+}
+
+///Move the player by a column and row offset if nothing is in the way
+///
+///Returns true if the player moved
+pub fn move_by(
+    game: &mut Game,
+    dx: i32,
+    dy: i32,
+) -> bool {
+    let level = game.levels.level(game.position.location());
+    let x = game.position.x + dx;
+    let y = game.position.y + dy;
+    if x < 0
+        || y < 0
+        || x >= level.width() as i32
+        || y >= level.height() as i32
+        || level
+            .creature_at(Point {
+                col: x as usize,
+                row: y as usize,
+            })
+            .is_some()
+        || !level.can_move_to(
+            Point {
+                col: x as usize,
+                row: y as usize,
+            },
+            Point {
+                col: game.position.x as usize,
+                row: game.position.y as usize,
+            },
+        )
+    {
+        return false;
+    }
+    //Standing up takes the move
+    if game.character.conditions.remove(Condition::Prone) {
+        game.take_action(Action::Move);
+        game.message(
+            Severity::Info,
+            Category::Movement,
+            "You get back on your feet",
+        );
+        return false;
+    }
+    if game.character.movement_speed(&game.races) == 0 {
+        game.message(
+            Severity::Warning,
+            Category::Movement,
+            "You are held fast and can not move",
+        );
+        return false;
+    }
+    game.position.x = x;
+    game.position.y = y;
+    game.take_action(Action::Move);
+
+    //Pick up anything lying here
+    let p = Point {
+        col: x as usize,
+        row: y as usize,
+    };
+    for item in game.levels.level(game.position.location()).take_items(p) {
+        game.message(
+            Severity::Info,
+            Category::General,
+            &format!("You pick up {}", item.name()),
+        );
+        game.character.pick_up(item);
+    }
+    true
+}
+
+///Index of a creature next to the player in a direction
+fn creature_in_direction(
+    game: &mut Game,
+    dx: i32,
+    dy: i32,
+) -> Option<usize> {
+    let x = game.position.x + dx;
+    let y = game.position.y + dy;
+    if x < 0 || y < 0 {
+        return None;
+    }
+    game.levels
+        .level(game.position.location())
+        .creature_at(Point {
+            col: x as usize,
+            row: y as usize,
+        })
+}
 
 pub fn step(
     game: &mut Game,
     screen: &mut Screen,
 ) -> bool {
-    //Increment AP points for actor and creatures
-    game.character.action_points_increment(1);
+    //A save kept after death holds nothing more to play
+    if game.character.is_dead(&game.races, &game.classes) {
+        screen.set_msg("You have died...");
+        screen.draw_display();
+        return true;
+    }
+
+    //Start the turns going on a new or loaded game
+    if game.scheduler.is_empty() {
+        game.scheduler.start(game.time as u64 * TICKS_PER_SECOND);
+        game.scheduler.schedule(Actor::Player, 0);
+        game.schedule_creatures();
+    }
+
+    //The player's turn has come round
+    game.next_actor();
+    game.player_action = None;
+
+    //A rest nobody disturbed is over
+    if let Some(long) = game.resting.take() {
+        finish_rest(game, screen, long);
+    }
+
+    //Things allowed once a round come back at the start of each
+    let round = game.time / ROUND_SECONDS;
+    if round != game.round {
+        game.round = round;
+        game.loading_fired = false;
+        game.off_hand_attacked = false;
+        game.sneak_attacked = false;
+    }
+
+    //Conditions wear off with time
+    for condition in game.character.conditions.expire(game.time) {
+        game.message(
+            Severity::Good,
+            Category::Combat,
+            &format!("You are no longer {}", condition.name()),
+        );
+    }
+
+    //Stop anything automatic once something new comes into view
+    interrupt_activity(game);
 
-    //If actor has met AP threshold, get input and process move
-    //Loop until action points are used up
-    while game.character.action_points() >= 2 {
-        match screen.draw_enter_char("Move: wasd/ykuhbjnl Interact: <space> Nothing: . Quit: q") {
+    //The turn passes by without the player able to do anything
+    if !game.character.conditions.can_act() {
+        game.activity = None;
+        game.message(Severity::Danger, Category::Combat, "You are stunned");
+        game.take_action(Action::Wait);
+    }
+
+    //Get input until the player does something that takes time
+    while game.player_action.is_none() {
+        //Carry on with anything automatic before asking for input
+        if game.activity.is_some() {
+            screen.draw_update();
+            if continue_activity(game) {
+                continue;
+            }
+        }
+
+        //Level up once there is enough experience
+        if game.character.can_level_up() {
+            game.level_up(screen);
+        }
+
+        //Show anything logged or changed since the map was drawn
+        game.update_panels(screen);
+
+        match screen
+            .draw_enter_char("Move: wasd/ykuhbjnl Interact: <space> Wait: . Help: ? Quit: q")
+        {
             //force refresh
             'r' => screen.force_refresh(),
+            //Character sheet, takes no time
+            'c' => character_sheet(game, screen),
+            //Map overview and browsing, these take no time
+            'm' => overview(game, screen),
+            'M' => browse(game, screen),
+            'X' => export(game, screen),
+            //Message history, takes no time
+            'P' => history(game, screen),
             //Player chose to quit
             'q' => return true,
-            //Player chose to do nothing more
-            '.' => break,
-            //Move forward/left
-            'y' => {
-                if game.position.x != 0
-                    && game.position.y != 0
-                    && game
-                        .levels
-                        .level(game.position.level_number as usize)
-                        .can_move_to(
-                            Point {
-                                col: (game.position.x - 1) as usize,
-                                row: (game.position.y - 1) as usize,
-                            },
-                            Point {
-                                col: (game.position.x) as usize,
-                                row: game.position.y as usize,
-                            },
-                        )
-                {
-                    game.position.y -= 1;
-                    game.position.x -= 1;
-                    game.character.action_points_decrement(2);
-                }
-            }
-            //Move forward
-            'w' | 'k' => {
-                if game.position.y != 0
-                    && game
-                        .levels
-                        .level(game.position.level_number as usize)
-                        .can_move_to(
-                            Point {
-                                col: game.position.x as usize,
-                                row: (game.position.y - 1) as usize,
-                            },
-                            Point {
-                                col: (game.position.x) as usize,
-                                row: game.position.y as usize,
-                            },
-                        )
-                {
-                    game.position.y -= 1;
-                    game.character.action_points_decrement(2);
-                }
-            }
-            //Move forward/right
-            'u' => {
-                if game.position.x
-                    != game
-                        .levels
-                        .level(game.position.level_number as usize)
-                        .width() as i32
-                        - 1
-                    && game.position.y != 0
-                    && game
-                        .levels
-                        .level(game.position.level_number as usize)
-                        .can_move_to(
-                            Point {
-                                col: (game.position.x + 1) as usize,
-                                row: (game.position.y - 1) as usize,
-                            },
-                            Point {
-                                col: (game.position.x) as usize,
-                                row: game.position.y as usize,
-                            },
-                        )
-                {
-                    game.position.y -= 1;
-                    game.position.x += 1;
-                    game.character.action_points_decrement(2);
-                }
-            }
-            //Move left
-            'a' | 'h' => {
-                if game.position.x != 0
-                    && game
-                        .levels
-                        .level(game.position.level_number as usize)
-                        .can_move_to(
-                            Point {
-                                col: (game.position.x - 1) as usize,
-                                row: game.position.y as usize,
-                            },
-                            Point {
-                                col: (game.position.x) as usize,
-                                row: game.position.y as usize,
-                            },
-                        )
-                {
-                    game.position.x -= 1;
-                    game.character.action_points_decrement(2);
-                }
-            }
-            //Move right
-            'd' | 'l' => {
-                if game.position.x
-                    != game
-                        .levels
-                        .level(game.position.level_number as usize)
-                        .width() as i32
-                        - 1
-                    && game
-                        .levels
-                        .level(game.position.level_number as usize)
-                        .can_move_to(
-                            Point {
-                                col: (game.position.x + 1) as usize,
-                                row: game.position.y as usize,
-                            },
-                            Point {
-                                col: (game.position.x) as usize,
-                                row: game.position.y as usize,
-                            },
-                        )
-                {
-                    game.position.x += 1;
-                    game.character.action_points_decrement(2);
-                }
-            }
-            //Move down/left
-            'b' => {
-                if game.position.x != 0
-                    && game.position.y
-                        != game
-                            .levels
-                            .level(game.position.level_number as usize)
-                            .height() as i32
-                            - 1
-                    && game
-                        .levels
-                        .level(game.position.level_number as usize)
-                        .can_move_to(
-                            Point {
-                                col: (game.position.x - 1) as usize,
-                                row: (game.position.y + 1) as usize,
-                            },
-                            Point {
-                                col: (game.position.x) as usize,
-                                row: game.position.y as usize,
-                            },
-                        )
-                {
-                    game.position.y += 1;
-                    game.position.x -= 1;
-                    game.character.action_points_decrement(2);
+            //Player chose to wait
+            '.' => game.take_action(Action::Wait),
+            //Travel to the nearest remembered staircase, or to a chosen position
+            key @ '<' | key @ '>' => {
+                if !travel_to_stairs(game, key == '<') {
+                    game.message(
+                        Severity::Warning,
+                        Category::Movement,
+                        &format!(
+                            "There is no known route to other stairs {}",
+                            if key == '<' { "up" } else { "down" }
+                        ),
+                    );
                 }
             }
-            //Move down
-            's' | 'j' => {
-                if game.position.y
-                    != game
-                        .levels
-                        .level(game.position.level_number as usize)
-                        .height() as i32
-                        - 1
-                    && game
-                        .levels
-                        .level(game.position.level_number as usize)
-                        .can_move_to(
-                            Point {
-                                col: game.position.x as usize,
-                                row: (game.position.y + 1) as usize,
-                            },
-                            Point {
-                                col: (game.position.x) as usize,
-                                row: game.position.y as usize,
-                            },
-                        )
-                {
-                    game.position.y += 1;
-                    game.character.action_points_decrement(2);
-                }
-            }
-            //Move down/right
-            'n' => {
-                if game.position.x
-                    != game
-                        .levels
-                        .level(game.position.level_number as usize)
-                        .width() as i32
-                        - 1
-                    && game.position.y
-                        != game
-                            .levels
-                            .level(game.position.level_number as usize)
-                            .height() as i32
-                            - 1
-                    && game
-                        .levels
-                        .level(game.position.level_number as usize)
-                        .can_move_to(
-                            Point {
-                                col: (game.position.x + 1) as usize,
-                                row: (game.position.y + 1) as usize,
-                            },
-                            Point {
-                                col: (game.position.x) as usize,
-                                row: game.position.y as usize,
-                            },
-                        )
-                {
-                    game.position.y += 1;
-                    game.position.x += 1;
-                    game.character.action_points_decrement(2);
+            '_' => {
+                if let Some(target) = select_position(game, screen, "Travel") {
+                    if !travel_to(game, target) {
+                        game.message(
+                            Severity::Warning,
+                            Category::Movement,
+                            "There is no known route there",
+                        );
+                    }
                 }
             }
+            //Explore until something interesting turns up
+            'o' => explore(game),
+            //Help, takes no time
+            '?' => help(screen),
+            //Choose weapons, takes no time
+            'e' => wield(game, screen),
+            //Attack something further off with a reach weapon
+            't' => reach_attack(game, screen),
+            //Class features, spells and resting
+            'F' => use_feature(game, screen),
+            'Z' => cast_spell(game, screen),
+            'f' => fire(game, screen),
+            'R' => rest(game, screen),
             //Interact
             ' ' => {
                 //Note: Use if else to avoid going down/up stairs, and for other future possible collisions
 
-                //Stairs Down
-                if game
-                    .levels
-                    .level(game.position.level_number as usize)
-                    .is_stair_down_at(game.position.x as usize, game.position.y as usize)
-                {
-                    game.position.level_number += 1;
-                    game.levels.level(game.position.level_number as usize); //Make sure level has been generated
-                    let position_p = game
-                        .levels
-                        .level_start_position(game.position.level_number as usize);
+                //Stairs
+                if let Some((location, position_p)) = game.levels.take_stair(
+                    game.position.location(),
+                    Point {
+                        col: game.position.x as usize,
+                        row: game.position.y as usize,
+                    },
+                ) {
+                    game.levels.level(game.position.location()).leave(game.time);
+                    game.position.branch = location.branch;
+                    game.position.level_number = location.level_number as i32;
                     game.position.x = position_p.col as i32;
                     game.position.y = position_p.row as i32;
-                    game.character.action_points_decrement(2);
+                    game.take_action(Action::Move);
+                    let branch_name = game.levels.branch_name(location.branch);
+                    game.message(
+                        Severity::Info,
+                        Category::Movement,
+                        &format!(
+                            "You take the stairs to {} L{}",
+                            branch_name.to_case(Case::Title),
+                            location.level_number
+                        ),
+                    );
+                    game.levels.level(location).catch_up(game.time);
+                    game.visit_level();
+                    game.schedule_creatures();
                 }
-                //Stairs Up
-                else if game
-                    .levels
-                    .level(game.position.level_number as usize)
-                    .is_stair_up_at(game.position.x as usize, game.position.y as usize)
-                    && game.position.level_number > 0
-                {
-                    game.position.level_number -= 1;
-                    game.levels.level(game.position.level_number as usize);
-                    let position_p = game
-                        .levels
-                        .level_exit_position(game.position.level_number as usize);
-                    game.position.x = position_p.col as i32;
-                    game.position.y = position_p.row as i32;
-                    game.character.action_points_decrement(2);
+            }
+            //Attack, move, run with shift, or ignore unrecognized keys
+            key => {
+                if let Some((dx, dy)) = direction(key) {
+                    if let Some(index) = creature_in_direction(game, dx, dy) {
+                        player_attack(game, index);
+                        game.take_action(Action::Attack);
+                    } else {
+                        move_by(game, dx, dy);
+                    }
+                } else if let Some((dx, dy)) = direction(key.to_ascii_lowercase()) {
+                    run(game, dx, dy);
                 }
             }
-            //Default, unrecognized key
-            _ => {}
         }
     }
 
-    //If creature has met AP threshold, get input and process move
+    //The player's next turn comes sooner the faster they move
+    if let Some(action) = game.player_action {
+        let speed = game.character.current_speed(&game.races);
+        game.scheduler
+            .schedule(Actor::Player, action.duration(speed));
+    }
 
-    //Increment AP points for next turn
-    game.character.action_points_increment(1);
+    //Everyone else takes their turns until it is the player's again
+    while let Some(Actor::Creature(id)) = game.scheduler.peek() {
+        game.next_actor();
+        creature_turn(game, id);
+        disturb_rest(game);
+        if game.character.is_dead(&game.races, &game.classes) {
+            game.activity = None;
+            game.message(Severity::Danger, Category::Combat, "You have died");
+            game.update_panels(screen);
+            screen.set_msg("You have died...");
+            screen.draw_display();
+            return true;
+        }
+    }
 
     false
 }
@@ -0,0 +1,324 @@
+use crate::game::messages::{Category, Severity};
+use crate::game::step::move_by;
+use crate::game::Game;
+use crate::levels::Level;
+use crate::utils::*;
+
+///Something the player keeps doing over several turns without input
+#[derive(Debug, Clone)]
+pub enum Activity {
+    ///Walk along a path, next step first
+    Travel(Vec<Point>),
+    ///Head for the nearest unexplored area, remembering how many stairs were known
+    Explore(usize),
+    ///Keep moving in a direction, following corridors
+    Run {
+        dx: i32,
+        dy: i32,
+        corridor: bool,
+        known_stairs: usize,
+    },
+}
+
+///Stop the current activity if something worth a look has come into view
+pub fn interrupt_activity(game: &mut Game) {
+    let level = game.levels.level(game.position.location());
+    //A creature coming into view is always worth stopping for, one already
+    //seen only while it is coming for the player
+    if game.activity.is_some() {
+        let hunting = level.creatures.iter().find(|creature| {
+            creature.aware && !creature.is_fleeing() && level.is_visible(creature.position)
+        });
+        if let Some(creature) = level.creature_came_into_view().or(hunting) {
+            let text = format!("You stop, you see a {}", creature.name());
+            game.activity = None;
+            game.message(Severity::Warning, Category::Exploration, &text);
+            return;
+        }
+    }
+    //Exploring and running are for getting somewhere, not for walking past loot
+    if let Some(Activity::Explore(_)) | Some(Activity::Run { .. }) = game.activity {
+        if let Some(item) = level.item_came_into_view() {
+            let text = format!("You stop, you see {}", item.name());
+            game.activity = None;
+            game.message(Severity::Info, Category::Exploration, &text);
+            return;
+        }
+    }
+    let interrupt = match game.activity {
+        Some(Activity::Travel(_)) if !level.newly_seen().is_empty() => {
+            Some("You stop, something new comes into view")
+        }
+        Some(Activity::Explore(known_stairs)) | Some(Activity::Run { known_stairs, .. })
+            if level.remembered_stairs().len() > known_stairs =>
+        {
+            Some("You stop, you have found a staircase")
+        }
+        //New corridor coming into view around a bend is only the way onward
+        Some(Activity::Run { .. })
+            if level
+                .newly_seen()
+                .iter()
+                .any(|&p| level.is_open(p.col as i32, p.row as i32) && !level.is_corridor(p)) =>
+        {
+            Some("You stop, something new comes into view")
+        }
+        _ => None,
+    };
+    if let Some(text) = interrupt {
+        game.activity = None;
+        game.message(Severity::Info, Category::Exploration, text);
+    }
+}
+
+///Start running in a direction
+pub fn run(
+    game: &mut Game,
+    dx: i32,
+    dy: i32,
+) {
+    let p = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+    let level = game.levels.level(game.position.location());
+    game.activity = Some(Activity::Run {
+        dx,
+        dy,
+        corridor: level.is_corridor(p),
+        known_stairs: level.remembered_stairs().len(),
+    });
+    //Always take the first step in the chosen direction
+    if !move_by(game, dx, dy) {
+        game.activity = None;
+    }
+}
+
+///Work out the next step while running, or None if running should stop
+///
+///In a corridor the only way onward is followed around bends, stopping at
+///junctions and dead ends. In a room running goes straight, stopping next to
+///doorways. Running stops on stairs and when moving between rooms and corridors
+fn run_step(
+    game: &mut Game,
+    dx: i32,
+    dy: i32,
+    corridor: bool,
+) -> Option<(i32, i32)> {
+    let p = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+    let (col, row) = (p.col as i32, p.row as i32);
+    let level = game.levels.level(game.position.location());
+
+    if level.stair_at(p).is_some() || level.is_corridor(p) != corridor {
+        return None;
+    }
+
+    if corridor {
+        let options: Vec<(i32, i32)> = [(0, -1), (0, 1), (-1, 0), (1, 0)]
+            .iter()
+            .filter(|(ox, oy)| {
+                //Never turn back the way we came
+                !((*ox != 0 && *ox == -dx) || (*oy != 0 && *oy == -dy))
+            })
+            .filter(|(ox, oy)| level.is_open(col + ox, row + oy))
+            .copied()
+            .collect();
+        if options.len() == 1 {
+            Some(options[0])
+        } else {
+            None
+        }
+    } else {
+        //Stop beside a doorway
+        for oy in -1..=1 {
+            for ox in -1..=1 {
+                if (ox == 0 && oy == 0) || (ox == dx && oy == dy) || (ox == -dx && oy == -dy) {
+                    continue;
+                }
+                if level.is_open(col + ox, row + oy)
+                    && level.is_corridor(Point {
+                        col: (col + ox) as usize,
+                        row: (row + oy) as usize,
+                    })
+                {
+                    return None;
+                }
+            }
+        }
+        Some((dx, dy))
+    }
+}
+
+///Start exploring
+pub fn explore(game: &mut Game) {
+    let known_stairs = game
+        .levels
+        .level(game.position.location())
+        .remembered_stairs()
+        .len();
+    game.activity = Some(Activity::Explore(known_stairs));
+}
+
+///Start travelling to a position along remembered tiles
+///
+///Returns false if there is no known way there
+pub fn travel_to(
+    game: &mut Game,
+    target: Point,
+) -> bool {
+    let from = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+    match game
+        .levels
+        .level(game.position.location())
+        .find_path(from, target, true)
+    {
+        Some(path) if !path.is_empty() => {
+            game.activity = Some(Activity::Travel(path));
+            true
+        }
+        _ => false,
+    }
+}
+
+///Start travelling to the nearest remembered staircase up or down
+pub fn travel_to_stairs(
+    game: &mut Game,
+    up: bool,
+) -> bool {
+    let from = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+    let level = game.levels.level(game.position.location());
+    let stairs: Vec<Point> = level
+        .remembered_stairs()
+        .iter()
+        .filter(|stair| stair.up == up)
+        .map(|stair| stair.position)
+        .collect();
+    match level.find_path_to_nearest(from, true, |p| stairs.contains(&p)) {
+        Some(path) if !path.is_empty() => {
+            game.activity = Some(Activity::Travel(path));
+            true
+        }
+        _ => false,
+    }
+}
+
+///Where exploring goes next: into an unseen tile next to the player, or
+///toward the nearest other tile next to something unseen
+fn explore_step(
+    level: &Level,
+    from: Point,
+) -> Option<Point> {
+    if let Some(next) = level
+        .neighbours(from, false)
+        .into_iter()
+        .find(|&p| !level.is_seen(p))
+    {
+        return Some(next);
+    }
+    match level.find_path_to_nearest(from, true, |p| p != from && level.is_frontier(p)) {
+        Some(path) if !path.is_empty() => Some(path[0]),
+        _ => None,
+    }
+}
+
+///Take the next step of the current activity
+///
+///Returns false, and stops the activity, once it is finished or blocked
+pub fn continue_activity(game: &mut Game) -> bool {
+    let from = Point {
+        col: game.position.x as usize,
+        row: game.position.y as usize,
+    };
+    let next = match game.activity.as_mut() {
+        Some(Activity::Travel(path)) if !path.is_empty() => path.remove(0),
+        Some(Activity::Explore(_)) => {
+            match explore_step(game.levels.level(game.position.location()), from) {
+                Some(next) => next,
+                None => {
+                    game.activity = None;
+                    game.message(
+                        Severity::Info,
+                        Category::Exploration,
+                        "Explored, nothing left to explore that can be reached",
+                    );
+                    return false;
+                }
+            }
+        }
+        Some(Activity::Run {
+            dx, dy, corridor, ..
+        }) => {
+            let (dx, dy, corridor) = (*dx, *dy, *corridor);
+            match run_step(game, dx, dy, corridor) {
+                Some((next_dx, next_dy)) => {
+                    if let Some(Activity::Run { dx, dy, .. }) = game.activity.as_mut() {
+                        *dx = next_dx;
+                        *dy = next_dy;
+                    }
+                    Point {
+                        col: (from.col as i32 + next_dx) as usize,
+                        row: (from.row as i32 + next_dy) as usize,
+                    }
+                }
+                None => {
+                    game.activity = None;
+                    return false;
+                }
+            }
+        }
+        _ => {
+            game.activity = None;
+            return false;
+        }
+    };
+
+    if !move_by(
+        game,
+        next.col as i32 - game.position.x,
+        next.row as i32 - game.position.y,
+    ) {
+        game.activity = None;
+        return false;
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(
+        col: usize,
+        row: usize,
+    ) -> Point {
+        Point { col, row }
+    }
+
+    #[test]
+    fn explore_steps_into_an_unseen_neighbour() {
+        let level = Level::from_rows(&["#####", "#..,#", "#####"]);
+        assert_eq!(explore_step(&level, p(2, 1)), Some(p(3, 1)));
+    }
+
+    #[test]
+    fn explore_moves_on_from_a_frontier_it_stands_on() {
+        //The unseen walls make every floor tile a frontier
+        let level = Level::from_rows(&["#####", "#...#", "#####"]);
+        assert_eq!(explore_step(&level, p(1, 1)), Some(p(2, 1)));
+    }
+
+    #[test]
+    fn explore_ends_with_nowhere_left() {
+        let level = Level::from_rows(&["###", "#.#", "###"]);
+        assert_eq!(explore_step(&level, p(1, 1)), None);
+    }
+}
@@ -0,0 +1,105 @@
+use convert_case::{Case, Casing};
+use serde::{Deserialize, Serialize};
+
+///Arrows, bolts and the like, fired from weapons with the ammunition property
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct Ammunition {
+    ammunition: String,
+    description: String,
+    //Copper pieces for a bundle
+    cost: u32,
+    //Pieces sold together
+    bundle: u32,
+    //Pounds for a bundle
+    weight: f32,
+    weapons: Vec<String>,
+    //Pieces carried or lying together
+    #[serde(default)]
+    quantity: u32,
+}
+
+impl Ammunition {
+    pub fn name(&self) -> String {
+        self.ammunition.clone()
+    }
+
+    pub fn quantity(&self) -> u32 {
+        self.quantity
+    }
+
+    ///Pounds for the pieces in this pile
+    pub fn weight(&self) -> f32 {
+        self.weight * self.quantity as f32 / self.bundle.max(1) as f32
+    }
+
+    ///Check if a weapon fires this ammunition
+    pub fn fits(
+        &self,
+        weapon: &str,
+    ) -> bool {
+        self.weapons.iter().any(|name| name == weapon)
+    }
+
+    ///Take some pieces off this pile, returning them as a pile of their own
+    pub fn split(
+        &mut self,
+        quantity: u32,
+    ) -> Ammunition {
+        let quantity = quantity.min(self.quantity);
+        self.quantity -= quantity;
+        Ammunition {
+            quantity,
+            ..self.clone()
+        }
+    }
+
+    ///Add another pile of the same ammunition to this one
+    pub fn merge(
+        &mut self,
+        other: &Ammunition,
+    ) {
+        self.quantity += other.quantity;
+    }
+
+    ///Name with how many there are, such as 3 arrows
+    pub fn detail_name(&self) -> String {
+        format!(
+            "{} {}\n",
+            self.quantity,
+            self.ammunition.to_case(Case::Title)
+        )
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Ammunitions {
+    ammunitions: Vec<Ammunition>,
+}
+
+impl Default for Ammunitions {
+    fn default() -> Self {
+        Ammunitions::new()
+    }
+}
+
+impl Ammunitions {
+    pub fn new() -> Ammunitions {
+        let ammunition_json = include_str!("ammunition.json");
+        let ammunitions: Vec<Ammunition> = serde_json::from_str(ammunition_json).unwrap();
+        Ammunitions { ammunitions }
+    }
+
+    ///A bundle of the ammunition a weapon fires, if it needs any
+    pub fn bundle_for(
+        &self,
+        weapon: &str,
+    ) -> Option<Ammunition> {
+        self.ammunitions
+            .iter()
+            .find(|ammunition| ammunition.fits(weapon))
+            .map(|ammunition| Ammunition {
+                quantity: ammunition.bundle,
+                ..ammunition.clone()
+            })
+    }
+}
@@ -1 +1,30 @@
-pub mod weapons;
\ No newline at end of file
+pub mod ammunition;
+pub mod weapons;
+
+use crate::items::ammunition::Ammunition;
+use crate::items::weapons::Weapon;
+use serde::{Deserialize, Serialize};
+
+///Something that can lie on the floor and be picked up
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum Item {
+    Weapon(Weapon),
+    Ammunition(Ammunition),
+}
+
+impl Item {
+    ///Name of the item, with how many there are for ammunition
+    pub fn name(&self) -> String {
+        match self {
+            Item::Weapon(weapon) => weapon.name(),
+            Item::Ammunition(ammunition) => ammunition.detail_name().trim().to_lowercase(),
+        }
+    }
+
+    pub fn symbol(&self) -> char {
+        match self {
+            Item::Weapon(_) => ')',
+            Item::Ammunition(_) => '{',
+        }
+    }
+}
@@ -1,4 +1,5 @@
 use crate::screen::COLUMN_WIDTH;
+use crate::utils::Dice;
 use convert_case::{Case, Casing};
 use serde::{Deserialize, Serialize};
 
@@ -51,6 +52,16 @@ impl Weapon {
         self.damage.clone()
     }
 
+    ///Damage dice, no damage if the weapon does none
+    pub fn damage_dice(&self) -> Dice {
+        Dice::parse(self.damage.split(' ').next().unwrap_or("")).unwrap_or_default()
+    }
+
+    ///Type of damage dealt, such as slashing
+    pub fn damage_type(&self) -> String {
+        self.damage.split(' ').nth(1).unwrap_or("").to_string()
+    }
+
     pub fn weight(&self) -> f32 {
         self.weight
     }
@@ -107,6 +118,14 @@ impl Weapon {
         self.properties.versatile_dmg.clone()
     }
 
+    ///Damage dice when wielded with two hands, None if the weapon is not versatile
+    pub fn versatile_dice(&self) -> Option<Dice> {
+        if !self.versatile() {
+            return None;
+        }
+        Dice::parse(&self.properties.versatile_dmg)
+    }
+
     pub fn detail_name(&self) -> String {
         format!("{}\n", self.name().to_case(Case::Title))
     }
@@ -247,6 +266,12 @@ pub struct Weapons {
     weapons: Vec<Weapon>,
 }
 
+impl Default for Weapons {
+    fn default() -> Self {
+        Weapons::new()
+    }
+}
+
 impl Weapons {
     pub fn new() -> Weapons {
         let weapon_json = include_str!("weapons.json");
@@ -0,0 +1,401 @@
+use serde::{Deserialize, Serialize};
+
+///Tiles needed around the largest room, for its walls and the level edge
+const ROOM_MARGIN: usize = 4;
+
+///Inclusive range of values
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize)]
+pub struct SizeRange {
+    pub min: usize,
+    pub max: usize,
+}
+
+impl SizeRange {
+    ///Check the range is not empty, naming it in the error
+    fn validate(
+        &self,
+        name: &str,
+    ) -> Result<(), String> {
+        if self.min > self.max {
+            return Err(format!(
+                "{} min {} is above max {}",
+                name, self.min, self.max
+            ));
+        }
+        Ok(())
+    }
+}
+
+///How often each feature is placed on a level
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DepthFeatures {
+    pub stairs_down: usize,
+}
+
+///Generator settings for a given depth
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct DepthSettings {
+    pub depth: usize,
+    pub height: usize,
+    pub width_ratio: f32,
+    pub rooms: usize,
+    pub room_width: SizeRange,
+    pub room_height: SizeRange,
+    pub long_hallway_chance: f64,
+    pub long_hallway_length: SizeRange,
+    pub features: DepthFeatures,
+}
+
+impl DepthSettings {
+    pub fn width(&self) -> usize {
+        (self.height as f32 * self.width_ratio).round() as usize
+    }
+
+    ///Check every range can be picked from and the level is big enough for
+    ///its rooms
+    fn validate(&self) -> Result<(), String> {
+        self.room_width.validate("room_width")?;
+        self.room_height.validate("room_height")?;
+        self.long_hallway_length.validate("long_hallway_length")?;
+        if !(0.0..=1.0).contains(&self.long_hallway_chance) {
+            return Err(format!(
+                "long_hallway_chance {} is not between 0 and 1",
+                self.long_hallway_chance
+            ));
+        }
+        self.validate_size(self.height)
+    }
+
+    ///Check the largest room fits in a level of a height, which levels
+    ///between this depth and the next can be generated with
+    fn validate_size(
+        &self,
+        height: usize,
+    ) -> Result<(), String> {
+        if height < self.room_height.max + ROOM_MARGIN {
+            return Err(format!(
+                "height {} is too small for rooms {} high",
+                height, self.room_height.max
+            ));
+        }
+        let width = (height as f32 * self.width_ratio).round() as usize;
+        if width < self.room_width.max + ROOM_MARGIN {
+            return Err(format!(
+                "width {} is too small for rooms {} wide",
+                width, self.room_width.max
+            ));
+        }
+        Ok(())
+    }
+}
+
+///Where a branch connects to its parent
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct BranchEntry {
+    pub branch: String,
+    pub depth: usize,
+}
+
+///A branch of the dungeon with its own progression curve
+///
+///Each depth entry applies from its depth onward, level size and room count are
+///interpolated between entries, everything else comes from the shallower entry
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Branch {
+    branch: String,
+    entry: Option<BranchEntry>,
+    levels: Option<usize>,
+    depths: Vec<DepthSettings>,
+}
+
+///Dungeon layout, made up of a main branch and any side branches
+///
+///The first branch is the main dungeon
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Dungeon {
+    branches: Vec<Branch>,
+}
+
+impl Default for Dungeon {
+    fn default() -> Self {
+        Dungeon::new()
+    }
+}
+
+impl Dungeon {
+    pub fn new() -> Dungeon {
+        let dungeon_json = include_str!("dungeon.json");
+        match Dungeon::load(dungeon_json) {
+            Ok(dungeon) => dungeon,
+            Err(error) => panic!("Invalid dungeon.json: {}", error),
+        }
+    }
+
+    ///Read a dungeon layout, checking it can be generated from
+    pub fn load(dungeon_json: &str) -> Result<Dungeon, String> {
+        let mut dungeon: Dungeon =
+            serde_json::from_str(dungeon_json).map_err(|error| error.to_string())?;
+        for branch in dungeon.branches.iter_mut() {
+            branch.depths.sort_by_key(|settings| settings.depth);
+        }
+        if dungeon.branches.is_empty() {
+            return Err("no branches".to_string());
+        }
+        for branch in dungeon.branches.iter() {
+            if branch.depths.is_empty() {
+                return Err(format!("branch {} has no depths", branch.branch));
+            }
+            for (index, settings) in branch.depths.iter().enumerate() {
+                //Levels up to the next depth keep these rooms as they shrink
+                //or grow toward its height
+                let next_height = branch.depths.get(index + 1).map(|next| next.height);
+                settings
+                    .validate()
+                    .and_then(|()| settings.validate_size(next_height.unwrap_or(settings.height)))
+                    .map_err(|error| {
+                        format!(
+                            "branch {} depth {}: {}",
+                            branch.branch, settings.depth, error
+                        )
+                    })?;
+            }
+            if let Some(entry) = &branch.entry {
+                let parent = match dungeon
+                    .branches
+                    .iter()
+                    .find(|parent| parent.branch == entry.branch)
+                {
+                    Some(parent) => parent,
+                    None => {
+                        return Err(format!(
+                            "branch {} is entered from unknown branch {}",
+                            branch.branch, entry.branch
+                        ))
+                    }
+                };
+                if let Some(levels) = parent.levels {
+                    if entry.depth >= levels {
+                        return Err(format!(
+                            "branch {} is entered from depth {} of branch {}, which has {} levels",
+                            branch.branch, entry.depth, entry.branch, levels
+                        ));
+                    }
+                }
+            }
+        }
+        Ok(dungeon)
+    }
+
+    pub fn branch_count(&self) -> usize {
+        self.branches.len()
+    }
+
+    pub fn branch_name(
+        &self,
+        branch: usize,
+    ) -> String {
+        self.branches[branch].branch.clone()
+    }
+
+    ///Branch and depth a branch is entered from, None for the main dungeon
+    pub fn branch_entry(
+        &self,
+        branch: usize,
+    ) -> Option<(usize, usize)> {
+        let entry = self.branches[branch].entry.as_ref()?;
+        let parent = self
+            .branches
+            .iter()
+            .position(|parent| parent.branch == entry.branch)
+            .unwrap();
+        Some((parent, entry.depth))
+    }
+
+    ///Branches entered from a level
+    pub fn branches_entered_at(
+        &self,
+        branch: usize,
+        level_number: usize,
+    ) -> Vec<usize> {
+        (0..self.branches.len())
+            .filter(|&child| self.branch_entry(child) == Some((branch, level_number)))
+            .collect()
+    }
+
+    ///Check if a branch goes down to a level
+    pub fn has_level(
+        &self,
+        branch: usize,
+        level_number: usize,
+    ) -> bool {
+        match self.branches[branch].levels {
+            Some(levels) => level_number < levels,
+            None => true,
+        }
+    }
+
+    ///Get the generator settings for a level
+    pub fn settings(
+        &self,
+        branch: usize,
+        level_number: usize,
+    ) -> DepthSettings {
+        let depths = &self.branches[branch].depths;
+        let lower = depths
+            .iter()
+            .rev()
+            .find(|settings| settings.depth <= level_number)
+            .unwrap_or(&depths[0]);
+        let mut settings = lower.clone();
+        settings.depth = level_number;
+
+        if let Some(upper) = depths.iter().find(|settings| settings.depth > level_number) {
+            let t = (level_number as f32 - lower.depth as f32)
+                / (upper.depth as f32 - lower.depth as f32);
+            settings.height = lerp(lower.height, upper.height, t);
+            settings.rooms = lerp(lower.rooms, upper.rooms, t);
+        }
+
+        //No stairs down past the bottom of the branch
+        if !self.has_level(branch, level_number + 1) {
+            settings.features.stairs_down = 0;
+        }
+
+        settings
+    }
+}
+
+///Linear interpolation between two values
+fn lerp(
+    a: usize,
+    b: usize,
+    t: f32,
+) -> usize {
+    (a as f32 + (b as f32 - a as f32) * t.clamp(0.0, 1.0)).round() as usize
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_room_width(room_width: &str) -> String {
+        format!(
+            r#"{{"branches": [{{"branch": "dungeon", "entry": null, "levels": null, "depths": [{{
+                "depth": 0, "height": 60, "width_ratio": 2.0, "rooms": 12,
+                "room_width": {}, "room_height": {{"min": 7, "max": 10}},
+                "long_hallway_chance": 0.05, "long_hallway_length": {{"min": 20, "max": 50}},
+                "features": {{"stairs_down": 1}}
+            }}]}}]}}"#,
+            room_width
+        )
+    }
+
+    ///The dungeon with one valid depth, changed before it is loaded
+    fn load_changed(change: impl Fn(&mut serde_json::Value)) -> Result<Dungeon, String> {
+        let mut json: serde_json::Value =
+            serde_json::from_str(&with_room_width(r#"{"min": 7, "max": 20}"#)).unwrap();
+        change(&mut json);
+        Dungeon::load(&json.to_string())
+    }
+
+    #[test]
+    fn bundled_dungeon_is_valid() {
+        let dungeon = Dungeon::load(include_str!("dungeon.json")).unwrap();
+        assert!(dungeon.branch_count() > 0);
+    }
+
+    #[test]
+    fn range_can_be_a_single_value() {
+        assert!(Dungeon::load(&with_room_width(r#"{"min": 7, "max": 7}"#)).is_ok());
+    }
+
+    #[test]
+    fn range_with_min_above_max_is_rejected() {
+        let error = Dungeon::load(&with_room_width(r#"{"min": 20, "max": 7}"#)).unwrap_err();
+        assert_eq!(
+            error,
+            "branch dungeon depth 0: room_width min 20 is above max 7"
+        );
+    }
+
+    #[test]
+    fn branch_without_depths_is_rejected() {
+        let json =
+            r#"{"branches": [{"branch": "dungeon", "entry": null, "levels": null, "depths": []}]}"#;
+        assert!(Dungeon::load(json).is_err());
+    }
+
+    #[test]
+    fn long_hallway_chance_is_a_probability() {
+        assert!(load_changed(|json| {
+            json["branches"][0]["depths"][0]["long_hallway_chance"] = 1.0.into()
+        })
+        .is_ok());
+        let error = load_changed(|json| {
+            json["branches"][0]["depths"][0]["long_hallway_chance"] = 1.5.into()
+        })
+        .unwrap_err();
+        assert_eq!(
+            error,
+            "branch dungeon depth 0: long_hallway_chance 1.5 is not between 0 and 1"
+        );
+    }
+
+    #[test]
+    fn level_must_fit_its_largest_room() {
+        //Rooms up to 10 high and 20 wide
+        assert!(
+            load_changed(|json| json["branches"][0]["depths"][0]["height"] = 14.into()).is_ok()
+        );
+        let error = load_changed(|json| json["branches"][0]["depths"][0]["height"] = 13.into())
+            .unwrap_err();
+        assert_eq!(
+            error,
+            "branch dungeon depth 0: height 13 is too small for rooms 10 high"
+        );
+        let error =
+            load_changed(|json| json["branches"][0]["depths"][0]["width_ratio"] = 0.3.into())
+                .unwrap_err();
+        assert_eq!(
+            error,
+            "branch dungeon depth 0: width 18 is too small for rooms 20 wide"
+        );
+    }
+
+    #[test]
+    fn levels_toward_a_smaller_depth_must_fit_the_rooms_too() {
+        let error = load_changed(|json| {
+            let mut next = json["branches"][0]["depths"][0].clone();
+            next["depth"] = 5.into();
+            next["height"] = 12.into();
+            next["room_height"] = serde_json::json!({"min": 3, "max": 8});
+            json["branches"][0]["depths"]
+                .as_array_mut()
+                .unwrap()
+                .push(next);
+        })
+        .unwrap_err();
+        assert_eq!(
+            error,
+            "branch dungeon depth 0: height 12 is too small for rooms 10 high"
+        );
+    }
+
+    #[test]
+    fn branch_entry_must_be_a_level_of_its_parent() {
+        let with_entry = |depth: usize| {
+            load_changed(move |json| {
+                json["branches"][0]["levels"] = 5.into();
+                let mut side = json["branches"][0].clone();
+                side["branch"] = "mines".into();
+                side["entry"] = serde_json::json!({"branch": "dungeon", "depth": depth});
+                json["branches"].as_array_mut().unwrap().push(side);
+            })
+        };
+        assert!(with_entry(4).is_ok());
+        assert_eq!(
+            with_entry(5).unwrap_err(),
+            "branch mines is entered from depth 5 of branch dungeon, which has 5 levels"
+        );
+    }
+}
@@ -0,0 +1,229 @@
+use crate::levels::level::Level;
+use crate::utils::*;
+use std::fs::{self, File};
+use std::io::{self, BufWriter};
+use std::path::Path;
+
+///Size in pixels of a tile in an exported image
+const PIXELS_PER_TILE: usize = 4;
+
+///File formats a level map can be exported to
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ExportFormat {
+    Text,
+    Html,
+    Png,
+}
+
+impl ExportFormat {
+    ///Pick a format from a file name extension
+    pub fn from_path(path: &str) -> Option<ExportFormat> {
+        match Path::new(path)
+            .extension()?
+            .to_str()?
+            .to_lowercase()
+            .as_str()
+        {
+            "txt" => Some(ExportFormat::Text),
+            "html" | "htm" => Some(ExportFormat::Html),
+            "png" => Some(ExportFormat::Png),
+            _ => None,
+        }
+    }
+
+    pub fn extension(&self) -> &str {
+        match self {
+            ExportFormat::Text => "txt",
+            ExportFormat::Html => "html",
+            ExportFormat::Png => "png",
+        }
+    }
+}
+
+///CSS class and color used for a map symbol
+fn symbol_style(symbol: char) -> (&'static str, [u8; 3]) {
+    match symbol {
+        '#' => ("wall", [96, 96, 96]),
+        '.' => ("floor", [200, 200, 200]),
+        '<' => ("up", [0, 200, 0]),
+        '>' => ("down", [220, 0, 0]),
+        _ => ("unseen", [0, 0, 0]),
+    }
+}
+
+impl Level {
+    ///Rows of symbols for the remembered map, or the whole map if revealing
+    fn export_rows(
+        &self,
+        reveal: bool,
+    ) -> Vec<Vec<char>> {
+        (0..self.height())
+            .map(|row| {
+                (0..self.width())
+                    .map(|col| self.remembered_symbol(Point { col, row }, reveal))
+                    .collect()
+            })
+            .collect()
+    }
+
+    ///Plain ASCII map
+    pub fn export_text(
+        &self,
+        reveal: bool,
+    ) -> String {
+        let mut text = String::new();
+        for row in self.export_rows(reveal) {
+            text.push_str(row.iter().collect::<String>().trim_end());
+            text.push('\n');
+        }
+        text
+    }
+
+    ///Standalone HTML page with a colored map
+    pub fn export_html(
+        &self,
+        reveal: bool,
+    ) -> String {
+        let mut html = String::new();
+        html.push_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n");
+        html.push_str("<title>Dungeon Crawler Map</title>\n<style>\n");
+        html.push_str("body { background: #000; }\n");
+        html.push_str("pre { font-family: monospace; line-height: 1; }\n");
+        for symbol in ['#', '.', '<', '>', ' '].iter() {
+            let (class, [r, g, b]) = symbol_style(*symbol);
+            html.push_str(&format!(
+                ".{} {{ color: #{:02x}{:02x}{:02x}; }}\n",
+                class, r, g, b
+            ));
+        }
+        html.push_str("</style>\n</head>\n<body>\n<pre>\n");
+
+        for row in self.export_rows(reveal) {
+            //Group runs of the same symbol into one span to keep the file small
+            let mut col = 0;
+            while col < row.len() {
+                let symbol = row[col];
+                let run = row[col..].iter().take_while(|&&c| c == symbol).count();
+                let text = match symbol {
+                    '<' => "&lt;".repeat(run),
+                    '>' => "&gt;".repeat(run),
+                    _ => symbol.to_string().repeat(run),
+                };
+                html.push_str(&format!(
+                    "<span class=\"{}\">{}</span>",
+                    symbol_style(symbol).0,
+                    text
+                ));
+                col += run;
+            }
+            html.push('\n');
+        }
+
+        html.push_str("</pre>\n</body>\n</html>\n");
+        html
+    }
+
+    ///Write a PNG image of the map
+    pub fn export_png(
+        &self,
+        file: &str,
+        reveal: bool,
+    ) -> io::Result<()> {
+        let rows = self.export_rows(reveal);
+        let width = self.width() * PIXELS_PER_TILE;
+        let height = self.height() * PIXELS_PER_TILE;
+
+        let mut data: Vec<u8> = Vec::with_capacity(width * height * 3);
+        for row in rows.iter() {
+            for _ in 0..PIXELS_PER_TILE {
+                for symbol in row.iter() {
+                    let (_, color) = symbol_style(*symbol);
+                    for _ in 0..PIXELS_PER_TILE {
+                        data.extend_from_slice(&color);
+                    }
+                }
+            }
+        }
+
+        let mut encoder = png::Encoder::new(
+            BufWriter::new(File::create(file)?),
+            width as u32,
+            height as u32,
+        );
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&data)?;
+        Ok(())
+    }
+
+    ///Export the map to a file
+    pub fn export(
+        &self,
+        file: &str,
+        format: ExportFormat,
+        reveal: bool,
+    ) -> io::Result<()> {
+        match format {
+            ExportFormat::Text => fs::write(file, self.export_text(reveal)),
+            ExportFormat::Html => fs::write(file, self.export_html(reveal)),
+            ExportFormat::Png => self.export_png(file, reveal),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::level::TileType;
+
+    ///A seen room with a staircase up, and unseen floor past its east wall
+    fn level() -> Level {
+        let mut level = Level::from_rows(&["....,", "....,"]);
+        level
+            .tiles
+            .set_tile_type(Point { col: 1, row: 0 }, TileType::StairUp);
+        level
+    }
+
+    #[test]
+    fn format_from_the_extension() {
+        assert_eq!(ExportFormat::from_path("map.txt"), Some(ExportFormat::Text));
+        assert_eq!(ExportFormat::from_path("map.HTM"), Some(ExportFormat::Html));
+        assert_eq!(
+            ExportFormat::from_path("dir/map.png"),
+            Some(ExportFormat::Png)
+        );
+        assert_eq!(ExportFormat::from_path("map.jpg"), None);
+        assert_eq!(ExportFormat::from_path("map"), None);
+    }
+
+    #[test]
+    fn text_shows_only_what_was_seen_unless_revealed() {
+        let level = level();
+        assert_eq!(level.export_text(false), ".<..\n....\n");
+        assert_eq!(level.export_text(true), ".<...\n.....\n");
+    }
+
+    #[test]
+    fn html_groups_runs_and_escapes_stairs() {
+        let html = level().export_html(false);
+        assert!(html.contains(
+            "<span class=\"floor\">.</span><span class=\"up\">&lt;</span>\
+             <span class=\"floor\">..</span><span class=\"unseen\"> </span>\n"
+        ));
+        assert!(html.contains("<span class=\"floor\">....</span><span class=\"unseen\"> </span>\n"));
+    }
+
+    #[test]
+    fn png_has_pixels_for_every_tile() {
+        let file = std::env::temp_dir().join("export_test_map.png");
+        let file = file.to_str().unwrap();
+        level().export(file, ExportFormat::Png, false).unwrap();
+        let decoder = png::Decoder::new(File::open(file).unwrap());
+        let reader = decoder.read_info().unwrap();
+        assert_eq!(reader.info().width as usize, 5 * PIXELS_PER_TILE);
+        assert_eq!(reader.info().height as usize, 2 * PIXELS_PER_TILE);
+        fs::remove_file(file).unwrap();
+    }
+}
@@ -1,21 +1,8 @@
+use crate::levels::dungeon::DepthSettings;
 use crate::levels::level::*;
 use crate::utils::*;
 use rand::Rng;
 
-///Generate level dimensions based on level number
-fn generate_width_and_height(level_number: usize) -> (usize, usize) {
-    //Start small, grow quickly, then stablize
-    //y = a / (1 + b e-kx ), k > 0
-    let a: f32 = 1000.0;
-    let b: f32 = 10.0;
-    let k: f32 = 0.75;
-    let one: f32 = 1.0;
-    let e: f32 = one.exp();
-    let height = (a / (1.0 + b * e.powf(-k * level_number as f32))).round() as usize;
-    let width = (height as f32 * 2.0).round() as usize;
-    (width, height)
-}
-
 fn room_collision(
     level: &mut Level,
     room_pos_x: usize,
@@ -26,8 +13,8 @@ fn room_collision(
     //Check for edge of world
     if room_pos_x < 1
         || room_pos_y < 1
-        || room_pos_x + room_dimension_x >= level.columns - 1
-        || room_pos_y + room_dimension_y >= level.rows - 1
+        || room_pos_x + room_dimension_x >= level.width() - 1
+        || room_pos_y + room_dimension_y >= level.height() - 1
     {
         return false;
     }
@@ -35,7 +22,23 @@ fn room_collision(
     //Check for any empty spaces within and around room
     for x in room_pos_x - 1..=room_pos_x + room_dimension_x + 1 {
         for y in room_pos_y - 1..=room_pos_y + room_dimension_y + 1 {
-            if level.tiles[y][x].tile != TileType::Wall {
+            if level.tiles.tile_type(Point { col: x, row: y }) != TileType::Wall {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+///Check that a 3x3 area around a position is all floor
+fn is_open_area(
+    level: &Level,
+    pos_x: usize,
+    pos_y: usize,
+) -> bool {
+    for y in pos_y - 1..=pos_y + 1 {
+        for x in pos_x - 1..=pos_x + 1 {
+            if level.tiles.tile_type(Point { col: x, row: y }) != TileType::Floor {
                 return false;
             }
         }
@@ -50,17 +53,18 @@ fn empty_out_area(
     dimension_x: usize,
     dimension_y: usize,
 ) {
-    for (y, tile_row) in level.tiles.iter_mut().enumerate() {
-        for (x, tile) in tile_row.iter_mut().enumerate() {
-            if pos_x <= x && pos_x + dimension_x > x && pos_y <= y && pos_y + dimension_y > y {
-                tile.tile = TileType::Floor;
-            }
+    for y in pos_y..pos_y + dimension_y {
+        for x in pos_x..pos_x + dimension_x {
+            level
+                .tiles
+                .set_tile_type(Point { col: x, row: y }, TileType::Floor);
         }
     }
 }
 
 fn empty_out_hallway_and_new_room(
     level: &mut Level,
+    settings: &DepthSettings,
     pos_x: usize,
     pos_y: usize,
     room_dimension_x: usize,
@@ -71,33 +75,43 @@ fn empty_out_hallway_and_new_room(
     //Check for a valid hallway is somewhere on a wall of a room, with no room on the other side
 
     //Never allow a hallway within 2 tiles of the edge
-    if pos_x < 2 || pos_y < 2 || pos_x >= level.columns - 2 || pos_y >= level.rows - 2 {
+    if pos_x < 2 || pos_y < 2 || pos_x >= level.width() - 2 || pos_y >= level.height() - 2 {
         return false;
     }
 
     //Hallway is on stone
-    if level.tiles[pos_y][pos_x].tile != TileType::Wall {
+    if level.tiles.tile_type(Point {
+        col: pos_x,
+        row: pos_y,
+    }) != TileType::Wall
+    {
         return false;
     }
 
     //Set hallway length
-    //95% of the time it should be length 2, long enough for there to be space between rooms
-    //5% randomize to something longer
-    let hallway_length = if rng.gen_bool(0.95) {
-        2
+    //Usually it should be length 2, long enough for there to be space between rooms
+    //Occasionally randomize to something longer
+    let hallway_length = if rng.gen_bool(settings.long_hallway_chance) {
+        rng.gen_range(settings.long_hallway_length.min..=settings.long_hallway_length.max)
     } else {
-        rng.gen_range(20..50)
+        2
     };
 
     //Hallway on top
-    if level.tiles[pos_y + 1][pos_x].tile != TileType::Wall
-        && level.tiles[pos_y - 1][pos_x].tile == TileType::Wall
+    if level.tiles.tile_type(Point {
+        col: pos_x,
+        row: pos_y + 1,
+    }) != TileType::Wall
+        && level.tiles.tile_type(Point {
+            col: pos_x,
+            row: pos_y - 1,
+        }) == TileType::Wall
     {
         //Check if room can fit on top
 
         //Pick random X offset and see if it can fit
         let pos_x_offset = rng.gen_range(0..room_dimension_x);
-        if pos_x <= pos_x_offset || pos_x + pos_x_offset >= level.columns {
+        if pos_x <= pos_x_offset || pos_x + pos_x_offset >= level.width() {
             return false;
         }
         let room_pos_x: usize = pos_x - pos_x_offset;
@@ -130,19 +144,25 @@ fn empty_out_hallway_and_new_room(
     }
 
     //Hallway on bottom
-    if level.tiles[pos_y - 1][pos_x].tile != TileType::Wall
-        && level.tiles[pos_y + 1][pos_x].tile == TileType::Wall
+    if level.tiles.tile_type(Point {
+        col: pos_x,
+        row: pos_y - 1,
+    }) != TileType::Wall
+        && level.tiles.tile_type(Point {
+            col: pos_x,
+            row: pos_y + 1,
+        }) == TileType::Wall
     {
         //Check if room can fit on bottom
 
         //Pick random X offset and see if it can fit
         let pos_x_offset = rng.gen_range(0..room_dimension_x);
-        if pos_x <= pos_x_offset || pos_x + pos_x_offset >= level.columns {
+        if pos_x <= pos_x_offset || pos_x + pos_x_offset >= level.width() {
             return false;
         }
         let room_pos_x: usize = pos_x - pos_x_offset;
 
-        if level.rows <= room_dimension_y + pos_y + hallway_length {
+        if level.height() <= room_dimension_y + pos_y + hallway_length {
             return false;
         }
         let room_pos_y: usize = pos_y + hallway_length;
@@ -170,8 +190,14 @@ fn empty_out_hallway_and_new_room(
     }
 
     //Hallway on left
-    if level.tiles[pos_y][pos_x + 1].tile != TileType::Wall
-        && level.tiles[pos_y][pos_x - 1].tile == TileType::Wall
+    if level.tiles.tile_type(Point {
+        col: pos_x + 1,
+        row: pos_y,
+    }) != TileType::Wall
+        && level.tiles.tile_type(Point {
+            col: pos_x - 1,
+            row: pos_y,
+        }) == TileType::Wall
     {
         //Check if room can fit on left
 
@@ -182,7 +208,7 @@ fn empty_out_hallway_and_new_room(
 
         //Pick random Y offset and see if it can fit
         let pos_y_offset = rng.gen_range(0..room_dimension_y);
-        if pos_y <= pos_y_offset || pos_y + pos_y_offset >= level.columns {
+        if pos_y <= pos_y_offset || pos_y + pos_y_offset >= level.width() {
             return false;
         }
         let room_pos_y: usize = pos_y - pos_y_offset;
@@ -210,20 +236,29 @@ fn empty_out_hallway_and_new_room(
     }
 
     //Hallway on right
-    if level.tiles[pos_y][pos_x - 1].tile != TileType::Wall
-        && level.tiles[pos_y][pos_x + 1].tile == TileType::Wall
-        && level.tiles[pos_y][pos_x + 2].tile == TileType::Wall
+    if level.tiles.tile_type(Point {
+        col: pos_x - 1,
+        row: pos_y,
+    }) != TileType::Wall
+        && level.tiles.tile_type(Point {
+            col: pos_x + 1,
+            row: pos_y,
+        }) == TileType::Wall
+        && level.tiles.tile_type(Point {
+            col: pos_x + 2,
+            row: pos_y,
+        }) == TileType::Wall
     {
         //Check if room can fit on right
 
-        if level.columns <= room_dimension_x + pos_x + hallway_length {
+        if level.width() <= room_dimension_x + pos_x + hallway_length {
             return false;
         }
         let room_pos_x: usize = pos_x + hallway_length + 1;
 
         //Pick random Y offset and see if it can fit
         let pos_y_offset = rng.gen_range(0..room_dimension_y);
-        if pos_y <= pos_y_offset || pos_y + pos_y_offset >= level.columns {
+        if pos_y <= pos_y_offset || pos_y + pos_y_offset >= level.width() {
             return false;
         }
         let room_pos_y: usize = pos_y - pos_y_offset;
@@ -253,40 +288,40 @@ fn empty_out_hallway_and_new_room(
     false
 }
 
-pub fn generate(level_number: usize) -> Level {
-    let (width, height) = generate_width_and_height(level_number);
+pub fn generate(
+    settings: &DepthSettings,
+    stairs: Vec<Stair>,
+) -> Level {
+    let width = settings.width();
+    let height = settings.height;
 
     //Create Level, full of unseen walls
     let mut level: Level = Level {
-        columns: width,
-        rows: height,
-        tiles: vec![
-            vec![
-                Tile {
-                    tile: TileType::Wall,
-                    seen: false
-                };
-                width
-            ];
-            height
-        ],
-        exit: Point { col: 0, row: 0 }, //TMP
+        tiles: TileMap::new(width, height),
         entrance: Point {
             col: width / 2,
             row: height / 2,
         },
+        stairs: Vec::new(),
+        creatures: Vec::new(),
+        items: Vec::new(),
+        left_at: 0,
+        newly_seen: Vec::new(),
+        visible: HashSet::new(),
+        came_into_view: Vec::new(),
+        creatures_in_view: Vec::new(),
+        creatures_came_into_view: Vec::new(),
     };
 
     let mut rng = rand::thread_rng();
-    let minimum_room_size = 7;
-    let maximum_room_size = 10;
 
     //Check if room collides with either the edge of the map or a non-walled space
 
     //Build first room
     //Make it near the center
-    let room_dimension_x: usize = rng.gen_range(minimum_room_size..=2 * maximum_room_size);
-    let room_dimension_y: usize = rng.gen_range(minimum_room_size..=maximum_room_size);
+    let room_dimension_x: usize = rng.gen_range(settings.room_width.min..=settings.room_width.max);
+    let room_dimension_y: usize =
+        rng.gen_range(settings.room_height.min..=settings.room_height.max);
     let room_position_x: usize =
         ((width as f32) / 2.0 - (room_dimension_x as f32) / 2.0).round() as usize;
     let room_position_y: usize =
@@ -311,12 +346,13 @@ pub fn generate(level_number: usize) -> Level {
             let hallway_pos_x: usize = rng.gen_range(2..=width - 2);
             let hallway_pos_y: usize = rng.gen_range(2..=height - 2);
             let room_dimension_x: usize =
-                rng.gen_range(minimum_room_size..=2 * maximum_room_size) as usize;
+                rng.gen_range(settings.room_width.min..=settings.room_width.max);
             let room_dimension_y: usize =
-                rng.gen_range(minimum_room_size..=maximum_room_size) as usize;
+                rng.gen_range(settings.room_height.min..=settings.room_height.max);
 
             if empty_out_hallway_and_new_room(
                 &mut level,
+                settings,
                 hallway_pos_x,
                 hallway_pos_y,
                 room_dimension_x,
@@ -334,41 +370,41 @@ pub fn generate(level_number: usize) -> Level {
                 count += 1;
             }
         }
-        if room_count > 10 + 50 * level_number || !room_created {
+        if room_count >= settings.rooms || !room_created {
             break;
         }
     }
 
-    //Build stairs up if not level 0
-    if level_number != 0 {
-        level.tiles[level.entrance.row as usize][level.entrance.col as usize].tile =
-            TileType::StairUp;
-    }
-
-    //Build stairs down somewhere
-    //Must have one, keep looping until we find space
-    loop {
-        let stairway_down_pos_x: usize = rng.gen_range(2..=width - 2);
-        let stairway_down_pos_y: usize = rng.gen_range(2..=height - 2);
-
-        //Want a 3x3 empty space
-        if level.tiles[stairway_down_pos_y - 1][stairway_down_pos_x - 1].tile == TileType::Floor
-            && level.tiles[stairway_down_pos_y - 1][stairway_down_pos_x].tile == TileType::Floor
-            && level.tiles[stairway_down_pos_y - 1][stairway_down_pos_x + 1].tile == TileType::Floor
-            && level.tiles[stairway_down_pos_y][stairway_down_pos_x - 1].tile == TileType::Floor
-            && level.tiles[stairway_down_pos_y][stairway_down_pos_x].tile == TileType::Floor
-            && level.tiles[stairway_down_pos_y][stairway_down_pos_x + 1].tile == TileType::Floor
-            && level.tiles[stairway_down_pos_y + 1][stairway_down_pos_x - 1].tile == TileType::Floor
-            && level.tiles[stairway_down_pos_y + 1][stairway_down_pos_x].tile == TileType::Floor
-            && level.tiles[stairway_down_pos_y + 1][stairway_down_pos_x + 1].tile == TileType::Floor
-        {
-            level.exit = Point {
-                col: stairway_down_pos_x,
-                row: stairway_down_pos_y,
-            };
-            level.tiles[stairway_down_pos_y][stairway_down_pos_x].tile = TileType::StairDown;
-            break;
+    //Build stairs
+    //The first staircase up sits at the entrance, the rest go anywhere with space
+    for mut stair in stairs {
+        if stair.up && level.stairs.iter().all(|placed| !placed.up) {
+            stair.position = level.entrance;
+        } else {
+            //Must have one, keep looping until we find space
+            loop {
+                let stairway_pos_x: usize = rng.gen_range(2..=width - 2);
+                let stairway_pos_y: usize = rng.gen_range(2..=height - 2);
+
+                //Want a 3x3 empty space
+                if is_open_area(&level, stairway_pos_x, stairway_pos_y) {
+                    stair.position = Point {
+                        col: stairway_pos_x,
+                        row: stairway_pos_y,
+                    };
+                    break;
+                }
+            }
         }
+        level.tiles.set_tile_type(
+            stair.position,
+            if stair.up {
+                TileType::StairUp
+            } else {
+                TileType::StairDown
+            },
+        );
+        level.stairs.push(stair);
     }
 
     level
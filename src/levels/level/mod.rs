@@ -1,8 +1,18 @@
+mod export;
 mod generation;
+mod pathfinding;
+mod tiles;
 
+use crate::actor::creatures::Creature;
+use crate::items::Item;
+use crate::levels::dungeon::DepthSettings;
+pub use crate::levels::level::export::ExportFormat;
 use crate::levels::level::generation::*;
+use crate::levels::level::tiles::*;
 use crate::utils::*;
+use rand::Rng;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 enum TileType {
@@ -22,18 +32,57 @@ struct Tile {
     tile: TileType,
     seen: bool,
 }
+///Branch and depth of a level within the dungeon
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+pub struct Location {
+    pub branch: usize,
+    pub level_number: usize,
+}
+
+///Staircase leading to a staircase on another level
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy)]
+pub struct Stair {
+    pub up: bool,
+    pub position: Point,
+    pub destination: Location,
+    pub destination_stair: usize,
+}
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Level {
-    columns: usize,
-    rows: usize,
-    tiles: Vec<Vec<Tile>>,
-    exit: Point,
+    tiles: TileMap,
     entrance: Point,
+    stairs: Vec<Stair>,
+    #[serde(default)]
+    pub creatures: Vec<Creature>,
+    //Items lying on the floor
+    #[serde(default)]
+    items: Vec<(Point, Item)>,
+    //Game time the player last left the level
+    #[serde(default)]
+    left_at: u32,
+    #[serde(skip)]
+    newly_seen: Vec<Point>,
+    //Tiles in view when the map vector was last generated
+    #[serde(skip)]
+    visible: HashSet<(usize, usize)>,
+    //Tiles in view that were not when the map vector was generated the time before
+    #[serde(skip)]
+    came_into_view: Vec<Point>,
+    //Ids of the creatures in view when the map vector was last generated
+    #[serde(skip)]
+    creatures_in_view: Vec<u32>,
+    //Ids of those among them that were not in view the time before
+    #[serde(skip)]
+    creatures_came_into_view: Vec<u32>,
 }
 
 impl Level {
-    pub fn new(level_number: usize) -> Level {
-        generate(level_number)
+    pub fn new(
+        settings: &DepthSettings,
+        stairs: Vec<Stair>,
+    ) -> Level {
+        generate(settings, stairs)
     }
 
     pub fn can_move_to(
@@ -48,68 +97,447 @@ impl Level {
         } else if (to.col == from.col && (to.row as i32 - from.row as i32).abs() == 1)
             || ((to.col as i32 - from.col as i32).abs() == 1 && to.row == from.row)
         {
-            return self.tiles[to.row][to.col].tile != TileType::Wall;
+            return self.tiles.tile_type(to) != TileType::Wall;
         //Moving diagonally
         // one of the corner squares between the points must not be a wall
         } else if (to.col as i32 - from.col as i32).abs() == 1
             && (to.row as i32 - from.row as i32).abs() == 1
         {
-            return (self.tiles[from.row][to.col].tile != TileType::Wall
-                || self.tiles[to.row][from.col].tile != TileType::Wall)
-                && self.tiles[to.row][to.col].tile != TileType::Wall;
+            return (self.tiles.tile_type(Point {
+                col: to.col,
+                row: from.row,
+            }) != TileType::Wall
+                || self.tiles.tile_type(Point {
+                    col: from.col,
+                    row: to.row,
+                }) != TileType::Wall)
+                && self.tiles.tile_type(to) != TileType::Wall;
         }
         false
     }
 
     pub fn width(&self) -> usize {
-        self.columns
+        self.tiles.width()
     }
 
     pub fn height(&self) -> usize {
-        self.rows
+        self.tiles.height()
     }
 
     ///Get the initial starting position of the level
+    ///
+    ///This is the first staircase up, or the center of the first room if there is none
     pub fn entrance(&self) -> Point {
         self.entrance
     }
 
-    ///Get the exit position of the level
-    pub fn exit(&self) -> Point {
-        self.exit
+    ///Get the position of a staircase
+    pub fn stair_position(
+        &self,
+        stair: usize,
+    ) -> Point {
+        self.stairs[stair].position
+    }
+
+    ///Get the staircase at a position, if there is one
+    pub fn stair_at(
+        &self,
+        p: Point,
+    ) -> Option<Stair> {
+        self.stairs
+            .iter()
+            .find(|stair| stair.position == p)
+            .copied()
+    }
+
+    ///Positions of staircases that have been seen
+    pub fn remembered_stairs(&self) -> Vec<Stair> {
+        self.stairs
+            .iter()
+            .filter(|stair| self.tiles.is_seen(stair.position))
+            .copied()
+            .collect()
+    }
+
+    ///Level drawn from rows of '.' for seen floor, ',' for unseen floor and
+    ///'#' for wall, for tests
+    #[cfg(test)]
+    pub fn from_rows(rows: &[&str]) -> Level {
+        let mut level = Level {
+            tiles: TileMap::new(rows[0].len(), rows.len()),
+            ..Default::default()
+        };
+        for (row, line) in rows.iter().enumerate() {
+            for (col, symbol) in line.chars().enumerate() {
+                let p = Point { col, row };
+                if symbol != '#' {
+                    level.tiles.set_tile_type(p, TileType::Floor);
+                }
+                if symbol == '.' {
+                    level.tiles.set_seen(p);
+                }
+            }
+        }
+        level
+    }
+
+    ///Check if a position is inside the level and not a wall
+    pub fn is_open(
+        &self,
+        col: i32,
+        row: i32,
+    ) -> bool {
+        col >= 0
+            && row >= 0
+            && col < self.width() as i32
+            && row < self.height() as i32
+            && self.tiles.tile_type(Point {
+                col: col as usize,
+                row: row as usize,
+            }) != TileType::Wall
+    }
+
+    ///Check that nothing but open tiles lies between two points
+    pub fn is_line_clear(
+        &self,
+        from: Point,
+        to: Point,
+    ) -> bool {
+        vec_between_points(&from, &to)
+            .iter()
+            .filter(|&&p| p != from && p != to)
+            .all(|p| self.is_open(p.col as i32, p.row as i32))
+    }
+
+    ///Points a projectile passes on its way from one point toward another
+    ///
+    ///The flight ends before a wall, or at the first creature in the way
+    pub fn projectile_path(
+        &self,
+        from: Point,
+        to: Point,
+    ) -> Vec<Point> {
+        let mut path: Vec<Point> = Vec::new();
+        for p in vec_between_points(&from, &to) {
+            if p == from {
+                continue;
+            }
+            if !self.is_open(p.col as i32, p.row as i32) {
+                break;
+            }
+            path.push(p);
+            if self.creature_at(p).is_some() {
+                break;
+            }
+        }
+        path
+    }
+
+    ///Leave an item on the floor, ammunition joining any pile of the same kind
+    pub fn drop_item(
+        &mut self,
+        p: Point,
+        item: Item,
+    ) {
+        if let Item::Ammunition(ammunition) = &item {
+            for (item_p, floor_item) in self.items.iter_mut() {
+                if let Item::Ammunition(pile) = floor_item {
+                    if *item_p == p && pile.name() == ammunition.name() {
+                        pile.merge(ammunition);
+                        return;
+                    }
+                }
+            }
+        }
+        self.items.push((p, item));
+    }
+
+    ///Pick up everything lying at a position
+    pub fn take_items(
+        &mut self,
+        p: Point,
+    ) -> Vec<Item> {
+        let (taken, left): (Vec<_>, Vec<_>) =
+            self.items.drain(..).partition(|(item_p, _)| *item_p == p);
+        self.items = left;
+        taken.into_iter().map(|(_, item)| item).collect()
+    }
+
+    ///Check if an open position is part of a corridor rather than a room
+    ///
+    ///Corridors are one tile wide, so unlike rooms they never form a 2x2 open block
+    pub fn is_corridor(
+        &self,
+        p: Point,
+    ) -> bool {
+        let (col, row) = (p.col as i32, p.row as i32);
+        if !self.is_open(col, row) {
+            return false;
+        }
+        for (dx, dy) in [(-1, -1), (1, -1), (-1, 1), (1, 1)].iter() {
+            if self.is_open(col + dx, row)
+                && self.is_open(col, row + dy)
+                && self.is_open(col + dx, row + dy)
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    pub fn is_seen(
+        &self,
+        p: Point,
+    ) -> bool {
+        self.tiles.is_seen(p)
     }
-    pub fn is_stair_down_at(
+
+    ///Check if a position is a seen open tile next to something not yet seen
+    pub fn is_frontier(
         &self,
-        x: usize,
-        y: usize,
+        p: Point,
     ) -> bool {
-        self.tiles[y][x].tile == TileType::StairDown
+        if !self.tiles.is_seen(p) || self.tiles.tile_type(p) == TileType::Wall {
+            return false;
+        }
+        for row in p.row.saturating_sub(1)..=(p.row + 1).min(self.height() - 1) {
+            for col in p.col.saturating_sub(1)..=(p.col + 1).min(self.width() - 1) {
+                if !self.tiles.is_seen(Point { col, row }) {
+                    return true;
+                }
+            }
+        }
+        false
     }
 
-    pub fn is_stair_up_at(
+    ///Check if a position was in view when the map vector was last generated
+    pub fn is_visible(
         &self,
-        x: usize,
-        y: usize,
+        p: Point,
     ) -> bool {
-        self.tiles[y][x].tile == TileType::StairUp
+        self.visible.contains(&(p.col, p.row))
+    }
+
+    ///Index of the creature at a position, if there is one
+    pub fn creature_at(
+        &self,
+        p: Point,
+    ) -> Option<usize> {
+        self.creatures
+            .iter()
+            .position(|creature| creature.position == p)
+    }
+
+    ///Index of the creature with an id, None once it is gone
+    pub fn creature_index(
+        &self,
+        id: u32,
+    ) -> Option<usize> {
+        self.creatures.iter().position(|creature| creature.id == id)
+    }
+
+    ///Give every creature without an id one no other creature here has
+    pub fn assign_creature_ids(&mut self) {
+        let mut next = self
+            .creatures
+            .iter()
+            .map(|creature| creature.id)
+            .max()
+            .unwrap_or(0);
+        for creature in self
+            .creatures
+            .iter_mut()
+            .filter(|creature| creature.id == 0)
+        {
+            next += 1;
+            creature.id = next;
+        }
+    }
+
+    ///Note the time the player leaves the level
+    pub fn leave(
+        &mut self,
+        time: u32,
+    ) {
+        self.left_at = time;
+    }
+
+    ///Bring the creatures up to date when the player returns, rather than
+    ///playing out every turn they missed
+    ///
+    ///They heal a hit point a minute, and lose track of the player after one.
+    ///Their conditions wear off as usual
+    pub fn catch_up(
+        &mut self,
+        time: u32,
+    ) {
+        for creature in self.creatures.iter_mut() {
+            creature.conditions.expire(time);
+        }
+        let minutes = time.saturating_sub(self.left_at) / 60;
+        if minutes == 0 {
+            return;
+        }
+        for creature in self.creatures.iter_mut() {
+            creature.heal(minutes);
+            creature.aware = false;
+            creature.last_seen = None;
+        }
+    }
+
+    ///A random floor tile with nothing on it, at least a distance away from a point
+    pub fn random_empty_floor(
+        &self,
+        away_from: Point,
+        min_distance: usize,
+    ) -> Option<Point> {
+        let mut rng = rand::thread_rng();
+        for _ in 0..1000 {
+            let p = Point {
+                col: rng.gen_range(0..self.width()),
+                row: rng.gen_range(0..self.height()),
+            };
+            if self.tiles.tile_type(p) == TileType::Floor
+                && distance(&p, &away_from) >= min_distance
+                && self.creature_at(p).is_none()
+            {
+                return Some(p);
+            }
+        }
+        None
+    }
+
+    ///Tiles seen for the first time when the map vector was last generated
+    pub fn newly_seen(&self) -> &[Point] {
+        &self.newly_seen
+    }
+
+    ///First item lying among the tiles that came into view when the map
+    ///vector was last generated
+    pub fn item_came_into_view(&self) -> Option<&Item> {
+        self.items
+            .iter()
+            .find(|(p, _)| self.came_into_view.contains(p))
+            .map(|(_, item)| item)
+    }
+
+    ///First creature that came into view when the map vector was last
+    ///generated
+    pub fn creature_came_into_view(&self) -> Option<&Creature> {
+        self.creatures
+            .iter()
+            .find(|creature| self.creatures_came_into_view.contains(&creature.id))
+    }
+
+    ///Describe what is remembered at a position
+    pub fn describe(
+        &self,
+        p: Point,
+    ) -> String {
+        if !self.tiles.is_seen(p) {
+            return "unexplored".to_string();
+        }
+        if let Some(creature) = self
+            .creature_at(p)
+            .map(|index| &self.creatures[index])
+            .filter(|creature| self.is_visible(creature.position))
+        {
+            return format!(
+                "{}, {} of {} hit points",
+                creature.name(),
+                creature.hit_points(),
+                creature.max_hit_points()
+            );
+        }
+        let items: Vec<String> = self
+            .items
+            .iter()
+            .filter(|(item_p, _)| *item_p == p)
+            .map(|(_, item)| item.name())
+            .collect();
+        if !items.is_empty() {
+            return items.join(", ");
+        }
+        match self.tiles.tile_type(p) {
+            TileType::Floor => "floor",
+            TileType::Wall => "wall",
+            TileType::StairUp => "staircase up",
+            TileType::StairDown => "staircase down",
+        }
+        .to_string()
+    }
+
+    ///Symbol for a remembered tile, blank if it has not been seen unless revealing everything
+    fn remembered_symbol(
+        &self,
+        p: Point,
+        reveal: bool,
+    ) -> char {
+        let tile = self.tiles.get(p);
+        if !tile.seen && !reveal {
+            return ' ';
+        }
+        match tile.tile {
+            TileType::Floor => '.',
+            TileType::Wall => '#',
+            TileType::StairUp => '<',
+            TileType::StairDown => '>',
+        }
+    }
+
+    ///Generate a scaled down map vector of everything remembered
+    ///
+    ///Each symbol covers a block of tiles, and shows the most interesting thing in it
+    pub fn overview_vec(
+        &self,
+        columns: usize,
+        rows: usize,
+        player_pos_p: &Point,
+    ) -> Vec<Vec<char>> {
+        let block_columns = self.width().div_ceil(columns).max(1);
+        let block_rows = self.height().div_ceil(rows).max(1);
+        let priority = ['@', '>', '<', '.', '#', ' '];
+
+        let mut overview_vec = vec![
+            vec![' '; self.width().div_ceil(block_columns)];
+            self.height().div_ceil(block_rows)
+        ];
+        for (block_row, overview_row) in overview_vec.iter_mut().enumerate() {
+            for (block_col, symbol) in overview_row.iter_mut().enumerate() {
+                for row in block_row * block_rows..((block_row + 1) * block_rows).min(self.height())
+                {
+                    for col in block_col * block_columns
+                        ..((block_col + 1) * block_columns).min(self.width())
+                    {
+                        let p = Point { col, row };
+                        let tile_symbol = if p == *player_pos_p {
+                            '@'
+                        } else {
+                            self.remembered_symbol(p, false)
+                        };
+                        let rank = |c: char| priority.iter().position(|&x| x == c).unwrap();
+                        if rank(tile_symbol) < rank(*symbol) {
+                            *symbol = tile_symbol;
+                        }
+                    }
+                }
+            }
+        }
+        overview_vec
     }
 
     ///Generate map vector with symbols
-    ///Updates seen vector within here
+    ///Updates seen vector within here, out to a view distance in tiles
     pub fn map_vec(
         &mut self,
         player_pos_p: &Point,
+        view_distance: usize,
     ) -> Vec<Vec<char>> {
         let mut map_vec = vec![vec![' '; self.width()]; self.height()];
 
         //Determine what we can see
-        let mut map_visible = vec![vec![false; self.width()]; self.height()];
-        //TODO Determine view distance
-        //Thinking that for vision, treat each cell as 3 ft
-        //darkvision can view out to 60ft
-        //normal vision out to 20 ft
-        //with torch out to 100ft
-        let view_distance = 100 / 3;
+        let mut map_visible: HashSet<(usize, usize)> = HashSet::new();
+        self.newly_seen.clear();
 
         #[allow(clippy::needless_range_loop)]
         //Start check within a square box around the player
@@ -127,7 +555,8 @@ impl Level {
             {
                 //If cell is out of range skip to the next one
                 //or if cell already visible skip to next one
-                if col >= self.width() || row >= self.height() || map_visible[row][col] {
+                if col >= self.width() || row >= self.height() || map_visible.contains(&(col, row))
+                {
                     continue;
                 }
 
@@ -140,11 +569,13 @@ impl Level {
                     //Walk through vector of points from player out to point
                     for p in vec_between_points(player_pos_p, &Point { col, row }) {
                         //Mark current point as both visible and seen
-                        map_visible[p.row as usize][p.col as usize] = true;
-                        self.tiles[p.row as usize][p.col as usize].seen = true;
+                        map_visible.insert((p.col, p.row));
+                        if self.tiles.set_seen(p) {
+                            self.newly_seen.push(p);
+                        }
 
                         //If we are at a wall, we can see no further
-                        if self.tiles[p.row as usize][p.col as usize].tile == TileType::Wall {
+                        if self.tiles.tile_type(p) == TileType::Wall {
                             break;
                         }
                     }
@@ -155,30 +586,88 @@ impl Level {
         #[allow(clippy::needless_range_loop)]
         for y in 0..self.height() {
             for x in 0..self.width() {
-                if self.tiles[y][x].tile == TileType::Floor {
-                    if map_visible[y][x] {
-                        map_vec[y][x] = '.';
-                    } else if self.tiles[y][x].seen {
-                        map_vec[y][x] = ':';
-                    }
-                }
-                if self.tiles[y][x].tile == TileType::StairUp
-                    && (map_visible[y][x] || self.tiles[y][x].seen)
-                {
-                    map_vec[y][x] = '<';
-                }
-                if self.tiles[y][x].tile == TileType::StairDown
-                    && (map_visible[y][x] || self.tiles[y][x].seen)
-                {
-                    map_vec[y][x] = '>';
-                }
-                if self.tiles[y][x].tile == TileType::Wall
-                    && (map_visible[y][x] || self.tiles[y][x].seen)
-                {
-                    map_vec[y][x] = '#';
+                let tile = self.tiles.get(Point { col: x, row: y });
+                let visible = map_visible.contains(&(x, y));
+                if !visible && !tile.seen {
+                    continue;
                 }
+                map_vec[y][x] = match tile.tile {
+                    TileType::Floor => {
+                        if visible {
+                            '.'
+                        } else {
+                            ':'
+                        }
+                    }
+                    TileType::StairUp => '<',
+                    TileType::StairDown => '>',
+                    TileType::Wall => '#',
+                };
             }
         }
+
+        //Items stay where they were last seen
+        for (p, item) in self.items.iter() {
+            if self.tiles.is_seen(*p) {
+                map_vec[p.row][p.col] = item.symbol();
+            }
+        }
+
+        //Creatures are only shown while in view, told apart by id even if
+        //placed since they were last queued
+        self.assign_creature_ids();
+        let mut creatures_in_view: Vec<u32> = Vec::new();
+        for creature in self.creatures.iter() {
+            if map_visible.contains(&(creature.position.col, creature.position.row)) {
+                map_vec[creature.position.row][creature.position.col] = creature.symbol();
+                creatures_in_view.push(creature.id);
+            }
+        }
+        self.creatures_came_into_view = creatures_in_view
+            .iter()
+            .filter(|id| !self.creatures_in_view.contains(id))
+            .copied()
+            .collect();
+        self.creatures_in_view = creatures_in_view;
+        self.came_into_view = map_visible
+            .difference(&self.visible)
+            .map(|&(col, row)| Point { col, row })
+            .collect();
+        self.visible = map_visible;
+
         map_vec
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::levels::dungeon::Dungeon;
+    use std::time::Instant;
+
+    ///Generation time and tile memory of deeper and deeper levels, chunked
+    ///against what dense storage would take
+    ///
+    ///Run with `cargo test --release generation_benchmark -- --ignored --nocapture`
+    #[test]
+    #[ignore]
+    fn generation_benchmark() {
+        let dungeon = Dungeon::new();
+        for level_number in [0, 2, 5, 10] {
+            let settings = dungeon.settings(0, level_number);
+            let start = Instant::now();
+            let level = Level::new(&settings, Vec::new());
+            let elapsed = start.elapsed();
+            let (chunked, dense) = level.tiles.memory();
+            println!(
+                "L{:<2} {:>4}x{:<4} {:>4}ms {:>5}KiB -> {:>4}KiB",
+                level_number,
+                level.width(),
+                level.height(),
+                elapsed.as_millis(),
+                dense / 1024,
+                chunked / 1024
+            );
+        }
+    }
+}
@@ -0,0 +1,239 @@
+use crate::levels::level::Level;
+use crate::utils::*;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+impl Level {
+    ///Neighbouring points that can be moved to from a point
+    ///
+    ///If known_only is set, only tiles that have been seen are considered
+    pub fn neighbours(
+        &self,
+        p: Point,
+        known_only: bool,
+    ) -> Vec<Point> {
+        let mut neighbours: Vec<Point> = Vec::new();
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let col = p.col as i32 + dx;
+                let row = p.row as i32 + dy;
+                if (dx == 0 && dy == 0)
+                    || col < 0
+                    || row < 0
+                    || col >= self.width() as i32
+                    || row >= self.height() as i32
+                {
+                    continue;
+                }
+                let to = Point {
+                    col: col as usize,
+                    row: row as usize,
+                };
+                if (!known_only || self.tiles.is_seen(to)) && self.can_move_to(to, p) {
+                    neighbours.push(to);
+                }
+            }
+        }
+        neighbours
+    }
+
+    ///Walk back through the came from map to build the path to a point
+    ///
+    ///The starting point is not included
+    fn rebuild_path(
+        came_from: &HashMap<(usize, usize), Point>,
+        from: Point,
+        to: Point,
+    ) -> Vec<Point> {
+        let mut path: Vec<Point> = vec![to];
+        let mut current = to;
+        while let Some(&previous) = came_from.get(&(current.col, current.row)) {
+            if previous == from {
+                break;
+            }
+            path.push(previous);
+            current = previous;
+        }
+        path.reverse();
+        path
+    }
+
+    ///Find the shortest path between two points using A*
+    ///
+    ///Every step costs the same, diagonals included, so the heuristic is the
+    ///larger of the column and row distances
+    pub fn find_path(
+        &self,
+        from: Point,
+        to: Point,
+        known_only: bool,
+    ) -> Option<Vec<Point>> {
+        if from == to {
+            return Some(Vec::new());
+        }
+        let heuristic = |p: Point| {
+            (p.col as i32 - to.col as i32)
+                .abs()
+                .max((p.row as i32 - to.row as i32).abs()) as usize
+        };
+
+        let mut open: BinaryHeap<Reverse<(usize, usize, usize, usize)>> = BinaryHeap::new();
+        let mut came_from: HashMap<(usize, usize), Point> = HashMap::new();
+        let mut cost: HashMap<(usize, usize), usize> = HashMap::new();
+        open.push(Reverse((heuristic(from), 0, from.col, from.row)));
+        cost.insert((from.col, from.row), 0);
+
+        while let Some(Reverse((_, current_cost, col, row))) = open.pop() {
+            let current = Point { col, row };
+            if current == to {
+                return Some(Level::rebuild_path(&came_from, from, to));
+            }
+            if current_cost > cost[&(col, row)] {
+                continue;
+            }
+            for next in self.neighbours(current, known_only) {
+                let next_cost = current_cost + 1;
+                if cost
+                    .get(&(next.col, next.row))
+                    .is_none_or(|&c| next_cost < c)
+                {
+                    cost.insert((next.col, next.row), next_cost);
+                    came_from.insert((next.col, next.row), current);
+                    open.push(Reverse((
+                        next_cost + heuristic(next),
+                        next_cost,
+                        next.col,
+                        next.row,
+                    )));
+                }
+            }
+        }
+        None
+    }
+
+    ///Find the shortest path to the closest point matching a goal using Dijkstra
+    pub fn find_path_to_nearest<F>(
+        &self,
+        from: Point,
+        known_only: bool,
+        goal: F,
+    ) -> Option<Vec<Point>>
+    where
+        F: Fn(Point) -> bool,
+    {
+        let mut open: BinaryHeap<Reverse<(usize, usize, usize)>> = BinaryHeap::new();
+        let mut came_from: HashMap<(usize, usize), Point> = HashMap::new();
+        let mut cost: HashMap<(usize, usize), usize> = HashMap::new();
+        open.push(Reverse((0, from.col, from.row)));
+        cost.insert((from.col, from.row), 0);
+
+        while let Some(Reverse((current_cost, col, row))) = open.pop() {
+            let current = Point { col, row };
+            if goal(current) {
+                if current == from {
+                    return Some(Vec::new());
+                }
+                return Some(Level::rebuild_path(&came_from, from, current));
+            }
+            if current_cost > cost[&(col, row)] {
+                continue;
+            }
+            for next in self.neighbours(current, known_only) {
+                let next_cost = current_cost + 1;
+                if cost
+                    .get(&(next.col, next.row))
+                    .is_none_or(|&c| next_cost < c)
+                {
+                    cost.insert((next.col, next.row), next_cost);
+                    came_from.insert((next.col, next.row), current);
+                    open.push(Reverse((next_cost, next.col, next.row)));
+                }
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(
+        col: usize,
+        row: usize,
+    ) -> Point {
+        Point { col, row }
+    }
+
+    #[test]
+    fn path_goes_around_walls() {
+        let level = Level::from_rows(&["#######", "#..#..#", "#..#..#", "#.....#", "#######"]);
+        let path = level.find_path(p(1, 1), p(5, 1), false).unwrap();
+        assert_eq!(path.len(), 4);
+        assert_eq!(path.last(), Some(&p(5, 1)));
+        assert!(path.iter().all(|step| step.col != 3 || step.row == 3));
+    }
+
+    #[test]
+    fn path_to_where_it_starts_is_empty() {
+        let level = Level::from_rows(&["###", "#.#", "###"]);
+        assert_eq!(level.find_path(p(1, 1), p(1, 1), false), Some(Vec::new()));
+    }
+
+    #[test]
+    fn no_path_into_a_wall() {
+        let level = Level::from_rows(&["#####", "#...#", "#####"]);
+        assert_eq!(level.find_path(p(1, 1), p(2, 0), false), None);
+    }
+
+    #[test]
+    fn no_path_to_a_closed_off_room() {
+        let level = Level::from_rows(&["#######", "#..#..#", "#..#..#", "#######"]);
+        assert_eq!(level.find_path(p(1, 1), p(5, 2), false), None);
+    }
+
+    #[test]
+    fn known_only_keeps_to_seen_tiles() {
+        let level = Level::from_rows(&["#######", "#..,..#", "#######"]);
+        assert_eq!(level.find_path(p(1, 1), p(5, 1), true), None);
+        assert_eq!(
+            level
+                .find_path(p(1, 1), p(5, 1), false)
+                .map(|path| path.len()),
+            Some(4)
+        );
+    }
+
+    #[test]
+    fn nearest_of_several_goals() {
+        let level = Level::from_rows(&["#########", "#.......#", "#########"]);
+        let goals = [p(7, 1), p(3, 1)];
+        let path = level
+            .find_path_to_nearest(p(1, 1), false, |point| goals.contains(&point))
+            .unwrap();
+        assert_eq!(path, vec![p(2, 1), p(3, 1)]);
+    }
+
+    #[test]
+    fn nearest_goal_behind_a_wall_is_passed_over() {
+        let level = Level::from_rows(&["#########", "#..#....#", "#########"]);
+        let goals = [p(4, 1), p(2, 1)];
+        let path = level
+            .find_path_to_nearest(p(1, 1), false, |point| goals.contains(&point))
+            .unwrap();
+        assert_eq!(path, vec![p(2, 1)]);
+        assert_eq!(
+            level.find_path_to_nearest(p(1, 1), false, |point| point == p(4, 1)),
+            None
+        );
+    }
+
+    #[test]
+    fn standing_on_a_goal_needs_no_path() {
+        let level = Level::from_rows(&["###", "#.#", "###"]);
+        assert_eq!(
+            level.find_path_to_nearest(p(1, 1), false, |point| point == p(1, 1)),
+            Some(Vec::new())
+        );
+    }
+}
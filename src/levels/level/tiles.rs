@@ -0,0 +1,232 @@
+use crate::levels::level::{Tile, TileType};
+use crate::utils::*;
+use serde::{Deserialize, Serialize};
+
+///Width and height of a chunk in tiles
+pub const CHUNK_SIZE: usize = 32;
+
+///Tile returned for any position inside a chunk that was never allocated
+static SOLID_ROCK: Tile = Tile {
+    tile: TileType::Wall,
+    seen: false,
+};
+
+///Fixed size block of tiles
+#[derive(Serialize, Deserialize, Debug, Clone)]
+struct Chunk {
+    tiles: Vec<Tile>,
+}
+
+impl Chunk {
+    fn new() -> Chunk {
+        Chunk {
+            tiles: vec![SOLID_ROCK.clone(); CHUNK_SIZE * CHUNK_SIZE],
+        }
+    }
+}
+
+///Sparse tile storage
+///
+///The level is split into CHUNK_SIZE x CHUNK_SIZE chunks, chunks are only
+///allocated once something other than unseen solid rock is written into them
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct TileMap {
+    columns: usize,
+    rows: usize,
+    chunk_columns: usize,
+    chunks: Vec<Option<Chunk>>,
+}
+
+impl TileMap {
+    pub fn new(
+        columns: usize,
+        rows: usize,
+    ) -> TileMap {
+        let chunk_columns = columns.div_ceil(CHUNK_SIZE);
+        let chunk_rows = rows.div_ceil(CHUNK_SIZE);
+        TileMap {
+            columns,
+            rows,
+            chunk_columns,
+            chunks: vec![None; chunk_columns * chunk_rows],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.columns
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows
+    }
+
+    ///Index of the chunk holding the point, and the index of the tile within it
+    fn index(
+        &self,
+        p: Point,
+    ) -> (usize, usize) {
+        assert!(
+            p.col < self.columns && p.row < self.rows,
+            "Tile {:?} is outside of {}x{} level",
+            p,
+            self.columns,
+            self.rows
+        );
+        (
+            (p.row / CHUNK_SIZE) * self.chunk_columns + p.col / CHUNK_SIZE,
+            (p.row % CHUNK_SIZE) * CHUNK_SIZE + p.col % CHUNK_SIZE,
+        )
+    }
+
+    ///Get the tile at a point
+    pub fn get(
+        &self,
+        p: Point,
+    ) -> &Tile {
+        let (chunk, tile) = self.index(p);
+        match &self.chunks[chunk] {
+            Some(chunk) => &chunk.tiles[tile],
+            None => &SOLID_ROCK,
+        }
+    }
+
+    ///Get a mutable tile at a point, allocating its chunk if needed
+    pub fn get_mut(
+        &mut self,
+        p: Point,
+    ) -> &mut Tile {
+        let (chunk, tile) = self.index(p);
+        &mut self.chunks[chunk].get_or_insert_with(Chunk::new).tiles[tile]
+    }
+
+    ///Get the tile type at a point
+    pub fn tile_type(
+        &self,
+        p: Point,
+    ) -> TileType {
+        self.get(p).tile.clone()
+    }
+
+    ///Set the tile type at a point
+    ///
+    ///Writing solid rock into an unallocated chunk does not allocate it
+    pub fn set_tile_type(
+        &mut self,
+        p: Point,
+        tile_type: TileType,
+    ) {
+        let (chunk, _) = self.index(p);
+        if tile_type == TileType::Wall && self.chunks[chunk].is_none() {
+            return;
+        }
+        self.get_mut(p).tile = tile_type;
+    }
+
+    pub fn is_seen(
+        &self,
+        p: Point,
+    ) -> bool {
+        self.get(p).seen
+    }
+
+    ///Mark a point as seen, returns true if it had not been seen before
+    pub fn set_seen(
+        &mut self,
+        p: Point,
+    ) -> bool {
+        if self.is_seen(p) {
+            return false;
+        }
+        self.get_mut(p).seen = true;
+        true
+    }
+}
+
+#[cfg(test)]
+impl TileMap {
+    ///Bytes the tiles take up, next to what a dense grid of them would take
+    pub fn memory(&self) -> (usize, usize) {
+        let chunked = self.chunks.len() * std::mem::size_of::<Option<Chunk>>()
+            + self.chunks.iter().flatten().count()
+                * CHUNK_SIZE
+                * CHUNK_SIZE
+                * std::mem::size_of::<Tile>();
+        let dense = self.rows
+            * (std::mem::size_of::<Vec<Tile>>() + self.columns * std::mem::size_of::<Tile>());
+        (chunked, dense)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allocated(map: &TileMap) -> usize {
+        map.chunks.iter().flatten().count()
+    }
+
+    #[test]
+    fn new_map_is_unallocated_rock() {
+        let map = TileMap::new(100, 50);
+        assert_eq!(map.chunks.len(), 4 * 2);
+        assert_eq!(allocated(&map), 0);
+        assert_eq!(map.tile_type(Point { col: 99, row: 49 }), TileType::Wall);
+        assert!(!map.is_seen(Point { col: 0, row: 0 }));
+    }
+
+    #[test]
+    fn tiles_land_in_their_own_chunk() {
+        let mut map = TileMap::new(100, 50);
+        let edge = Point {
+            col: CHUNK_SIZE - 1,
+            row: CHUNK_SIZE - 1,
+        };
+        let next = Point {
+            col: CHUNK_SIZE,
+            row: CHUNK_SIZE,
+        };
+        map.set_tile_type(edge, TileType::Floor);
+        assert_eq!(allocated(&map), 1);
+        assert_eq!(map.tile_type(edge), TileType::Floor);
+        assert_eq!(map.tile_type(next), TileType::Wall);
+
+        map.set_tile_type(next, TileType::StairDown);
+        assert_eq!(allocated(&map), 2);
+        assert_eq!(map.index(next), (map.chunk_columns + 1, 0));
+        assert_eq!(map.tile_type(next), TileType::StairDown);
+        assert_eq!(map.tile_type(edge), TileType::Floor);
+    }
+
+    #[test]
+    fn partial_chunks_at_the_edges() {
+        let mut map = TileMap::new(40, 33);
+        let corner = Point { col: 39, row: 32 };
+        map.set_tile_type(corner, TileType::Floor);
+        assert_eq!(map.index(corner), (3, 7));
+        assert_eq!(map.tile_type(corner), TileType::Floor);
+    }
+
+    #[test]
+    fn writing_rock_does_not_allocate() {
+        let mut map = TileMap::new(64, 64);
+        map.set_tile_type(Point { col: 5, row: 5 }, TileType::Wall);
+        assert_eq!(allocated(&map), 0);
+    }
+
+    #[test]
+    fn seen_only_once() {
+        let mut map = TileMap::new(64, 64);
+        let p = Point { col: 40, row: 3 };
+        assert!(map.set_seen(p));
+        assert!(!map.set_seen(p));
+        assert!(map.is_seen(p));
+        assert_eq!(allocated(&map), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn outside_the_map_panics() {
+        let map = TileMap::new(64, 64);
+        map.get(Point { col: 64, row: 0 });
+    }
+}
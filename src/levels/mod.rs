@@ -1,51 +1,265 @@
+mod dungeon;
 mod level;
 
+use convert_case::{Case, Casing};
 use serde::{Deserialize, Serialize};
+use std::io;
 
+use crate::levels::dungeon::Dungeon;
 use crate::levels::level::*;
+pub use crate::levels::level::{ExportFormat, Level, Location};
 use crate::utils::*;
 
+///All generated levels, grouped by branch
+///
+///Staircases on each level are ordered as the stairs up, the stairs down,
+///then one staircase down into each branch entered from that level
+///
+///Every level generated is kept, in memory and in the save, for the rest of
+///the game. Unloading levels far from the player is not done, the sparse
+///tile chunks only keep down what each one costs
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct Levels {
-    level: Vec<Level>,
+    #[serde(skip)]
+    dungeon: Dungeon,
+    //Saves from before branches have none, their levels are generated anew
+    #[serde(default)]
+    branches: Vec<Vec<Level>>,
 }
 
 impl Levels {
     pub fn level(
         &mut self,
-        level_number: usize,
+        location: Location,
     ) -> &mut Level {
-        if level_number >= self.level.len() {
-            for number in self.level.len()..level_number + 1 {
-                let new_level = Level::new(number);
-                self.level.push(new_level)
+        if location.branch >= self.branches.len() {
+            self.branches
+                .resize_with(self.dungeon.branch_count(), Vec::new);
+        }
+        if location.level_number >= self.branches[location.branch].len() {
+            for number in self.branches[location.branch].len()..location.level_number + 1 {
+                let level_location = Location {
+                    branch: location.branch,
+                    level_number: number,
+                };
+                let new_level = Level::new(
+                    &self.dungeon.settings(location.branch, number),
+                    self.stairs(level_location),
+                );
+                self.branches[location.branch].push(new_level)
+            }
+        }
+        &mut self.branches[location.branch][location.level_number]
+    }
+
+    ///Get the name of the branch
+    pub fn branch_name(
+        &self,
+        branch: usize,
+    ) -> String {
+        self.dungeon.branch_name(branch)
+    }
+
+    ///Levels below the surface, counting the levels of the branches above
+    pub fn depth(
+        &self,
+        location: Location,
+    ) -> usize {
+        match self.dungeon.branch_entry(location.branch) {
+            Some((parent, depth)) => {
+                self.depth(Location {
+                    branch: parent,
+                    level_number: depth,
+                }) + 1
+                    + location.level_number
             }
+            None => location.level_number,
         }
-        &mut self.level[level_number]
+    }
+
+    ///Find a location from a branch name and level number
+    pub fn location(
+        &self,
+        branch_name: &str,
+        level_number: usize,
+    ) -> Option<Location> {
+        (0..self.dungeon.branch_count())
+            .find(|&branch| self.dungeon.branch_name(branch) == branch_name)
+            .map(|branch| Location {
+                branch,
+                level_number,
+            })
+            .filter(|location| self.dungeon.has_level(location.branch, level_number))
+    }
+
+    ///Export a level map to a file, the format is picked from the file extension
+    pub fn export(
+        &mut self,
+        location: Location,
+        file: &str,
+        reveal: bool,
+    ) -> io::Result<()> {
+        let format = ExportFormat::from_path(file).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "unknown map format, use .txt, .html or .png",
+            )
+        })?;
+        self.level(location).export(file, format, reveal)
+    }
+
+    ///Number of staircases up on a level
+    fn stairs_up_count(
+        &self,
+        location: Location,
+    ) -> usize {
+        if location.level_number > 0 {
+            self.dungeon
+                .settings(location.branch, location.level_number - 1)
+                .features
+                .stairs_down
+        } else if self.dungeon.branch_entry(location.branch).is_some() {
+            1
+        } else {
+            0
+        }
+    }
+
+    ///Number of staircases down on a level, not counting branch entrances
+    fn stairs_down_count(
+        &self,
+        location: Location,
+    ) -> usize {
+        self.dungeon
+            .settings(location.branch, location.level_number)
+            .features
+            .stairs_down
+    }
+
+    ///Work out where every staircase on a level leads
+    fn stairs(
+        &self,
+        location: Location,
+    ) -> Vec<Stair> {
+        let mut stairs: Vec<Stair> = Vec::new();
+
+        //Stairs up lead to the matching stairs down above, or back to the parent branch
+        for number in 0..self.stairs_up_count(location) {
+            if location.level_number > 0 {
+                let destination = Location {
+                    branch: location.branch,
+                    level_number: location.level_number - 1,
+                };
+                stairs.push(Stair {
+                    up: true,
+                    destination,
+                    destination_stair: self.stairs_up_count(destination) + number,
+                    ..Default::default()
+                });
+            } else {
+                let (branch, level_number) = self.dungeon.branch_entry(location.branch).unwrap();
+                let destination = Location {
+                    branch,
+                    level_number,
+                };
+                let branch_stair = self
+                    .dungeon
+                    .branches_entered_at(branch, level_number)
+                    .iter()
+                    .position(|&child| child == location.branch)
+                    .unwrap();
+                stairs.push(Stair {
+                    up: true,
+                    destination,
+                    destination_stair: self.stairs_up_count(destination)
+                        + self.stairs_down_count(destination)
+                        + branch_stair,
+                    ..Default::default()
+                });
+            }
+        }
+
+        //Stairs down lead to the matching stairs up below
+        for number in 0..self.stairs_down_count(location) {
+            stairs.push(Stair {
+                up: false,
+                destination: Location {
+                    branch: location.branch,
+                    level_number: location.level_number + 1,
+                },
+                destination_stair: number,
+                ..Default::default()
+            });
+        }
+
+        //Branch stairs lead to the top of the branch
+        for branch in self
+            .dungeon
+            .branches_entered_at(location.branch, location.level_number)
+        {
+            stairs.push(Stair {
+                up: false,
+                destination: Location {
+                    branch,
+                    level_number: 0,
+                },
+                destination_stair: 0,
+                ..Default::default()
+            });
+        }
+
+        stairs
     }
 
     ///Get the initial starting position of the level
     pub fn level_start_position(
         &mut self,
-        level_number: usize,
+        location: Location,
     ) -> Point {
-        self.level(level_number).entrance()
+        self.level(location).entrance()
     }
 
-    ///Get the exit position of the level
-    pub fn level_exit_position(
+    ///Follow the staircase at a position
+    ///
+    ///Returns the location and position arrived at, or None if there is no staircase
+    pub fn take_stair(
         &mut self,
-        level_number: usize,
-    ) -> Point {
-        self.level(level_number).exit()
+        location: Location,
+        p: Point,
+    ) -> Option<(Location, Point)> {
+        let stair = self.level(location).stair_at(p)?;
+        let position = self
+            .level(stair.destination)
+            .stair_position(stair.destination_stair);
+        Some((stair.destination, position))
+    }
+
+    ///Describe what is remembered at a position, including where stairs lead
+    pub fn describe(
+        &mut self,
+        location: Location,
+        p: Point,
+    ) -> String {
+        let description = self.level(location).describe(p);
+        match self.level(location).stair_at(p) {
+            Some(stair) if description != "unexplored" => format!(
+                "{} to {} L{}",
+                description,
+                self.branch_name(stair.destination.branch)
+                    .to_case(Case::Title),
+                stair.destination.level_number
+            ),
+            _ => description,
+        }
     }
 
     ///Generate map vector with symbols
     pub fn map_vec(
         &mut self,
-        level_number: usize,
+        location: Location,
         player_pos_p: &Point,
+        view_distance: usize,
     ) -> Vec<Vec<char>> {
-        self.level(level_number).map_vec(player_pos_p)
+        self.level(location).map_vec(player_pos_p, view_distance)
     }
 }
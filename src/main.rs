@@ -5,12 +5,8 @@ mod screen;
 mod utils;
 mod items;
 
-
-use crate::actor::player::classes::Classes;
 use crate::game::Game;
-use crate::actor::player::races::Races;
 use crate::screen::Screen;
-use crate::items::weapons::Weapons;
 use sm::sm;
 use std::env;
 use std::path::Path;
@@ -32,7 +28,7 @@ sm! {
         }
 
         Done {
-            Game => Finished
+            Load, Game => Finished
         }
     }
 }
@@ -40,27 +36,58 @@ use crate::GameState::{Variant::*, *};
 
 fn main() {
     //Process args
+    //  [save file] [--export-map <file> [--branch <name>] [--depth <number>] [--reveal]]
     let args: Vec<String> = env::args().collect();
+    let mut save_file: String = "default_save.json".to_string();
+    let mut export_file: Option<String> = None;
+    let mut export_branch: String = "dungeon".to_string();
+    let mut export_depth: usize = 0;
+    let mut export_reveal = false;
+    let mut args_iter = args.iter().skip(1);
+    while let Some(arg) = args_iter.next() {
+        match arg.as_str() {
+            "--export-map" => export_file = args_iter.next().cloned(),
+            "--branch" => export_branch = args_iter.next().cloned().unwrap_or(export_branch),
+            "--depth" => {
+                let depth = args_iter.next();
+                match depth.and_then(|depth| depth.parse().ok()) {
+                    Some(depth) => export_depth = depth,
+                    None => {
+                        println!(
+                            "Invalid depth '{}', expected a level number",
+                            depth.map_or("", |depth| depth.as_str())
+                        );
+                        return;
+                    }
+                }
+            }
+            "--reveal" => export_reveal = true,
+            _ => save_file = arg.to_string(),
+        }
+    }
 
-    //Process save file name
-    let save_file: String = if args.len() == 2 {
-        args[1].to_string()
-    } else {
-        "default_save.json".to_string()
-    };
+    //Export a level map and exit, using the saved game if there is one
+    if let Some(export_file) = export_file {
+        let mut game: Game = Game::new();
+        if Path::new(&save_file).exists() {
+            if let Err(e) = game.load(&save_file) {
+                println!("Unable to load '{}': {}", save_file, e);
+                return;
+            }
+        }
+        match game.levels.location(&export_branch, export_depth) {
+            Some(location) => match game.levels.export(location, &export_file, export_reveal) {
+                Ok(()) => println!("Exported map to '{}'", export_file),
+                Err(e) => println!("Unable to export map: {}", e),
+            },
+            None => println!("No level {} in branch '{}'", export_depth, export_branch),
+        }
+        return;
+    }
 
     //Load screen
     let mut screen: Screen = Screen::new();
 
-    //Load races
-    let races: Races = Races::new();
-
-    //Load classes
-    let classes: Classes = Classes::new();
-
-    //Load weapons
-    let weapons: Weapons = Weapons::new();
-
     let mut game: Game = Game::new();
 
     let mut sm = Machine::new(Idle).as_enum();
@@ -88,20 +115,31 @@ fn main() {
                 }
             }
 
-            LoadByLoadGame(m) => {
-                game.load(&save_file);
-                m.transition(LaunchGame).as_enum()
-            }
+            LoadByLoadGame(m) => match game.load(&save_file) {
+                Ok(()) => m.transition(LaunchGame).as_enum(),
+                //Leave the save alone rather than start over on top of it
+                Err(e) => {
+                    screen.set_msg(&format!("Unable to load '{}': {}", save_file, e));
+                    screen.draw_display();
+                    m.transition(Done).as_enum()
+                }
+            },
 
             CharacterByCreateCharacter(m) => {
-                game.character
-                    .new(&mut screen, &races, &classes, &weapons);
+                game.character = game.character.new(
+                    &mut screen,
+                    &game.races,
+                    &game.classes,
+                    &game.backgrounds,
+                    &game.weapons,
+                );
                 game.save(&save_file);
                 m.transition(LaunchGame).as_enum()
             }
 
             GameByLaunchGame(m) => {
                 game.run(&mut screen);
+                game.save(&save_file);
 
                 m.transition(Done).as_enum()
             }
@@ -0,0 +1,197 @@
+use crate::screen::{COLUMN_WIDTH, ROW_HEIGHT};
+
+///Area of the screen in cells
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Rect {
+    pub col: usize,
+    pub row: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Rect {
+    ///The whole screen
+    pub fn full() -> Rect {
+        Rect {
+            col: 0,
+            row: 0,
+            width: COLUMN_WIDTH,
+            height: ROW_HEIGHT,
+        }
+    }
+
+    ///Split rows off the top, returns the top and what is left
+    fn split_top(
+        self,
+        rows: usize,
+    ) -> (Rect, Rect) {
+        let rows = rows.min(self.height);
+        (
+            Rect {
+                height: rows,
+                ..self
+            },
+            Rect {
+                row: self.row + rows,
+                height: self.height - rows,
+                ..self
+            },
+        )
+    }
+
+    ///Split rows off the bottom, returns what is left and the bottom
+    fn split_bottom(
+        self,
+        rows: usize,
+    ) -> (Rect, Rect) {
+        self.split_top(self.height - rows.min(self.height))
+    }
+
+    ///Split columns off the right, returns what is left and the right
+    fn split_right(
+        self,
+        columns: usize,
+    ) -> (Rect, Rect) {
+        let columns = columns.min(self.width);
+        (
+            Rect {
+                width: self.width - columns,
+                ..self
+            },
+            Rect {
+                col: self.col + self.width - columns,
+                width: columns,
+                ..self
+            },
+        )
+    }
+}
+
+///Where each panel is drawn
+///
+///The header and footer span the screen, separated from the rest by lines.
+///The log panel sits above the footer, and the sidebar to the right of the
+///body which holds the message or map
+#[derive(Clone, Debug, Default)]
+pub struct Layout {
+    pub header: Rect,
+    pub body: Rect,
+    pub sidebar: Option<Rect>,
+    pub log: Option<Rect>,
+    pub footer: Rect,
+    separators: Vec<Rect>,
+    sidebar_separator: Option<Rect>,
+}
+
+impl Layout {
+    ///Panels with zero rows or columns are left out
+    pub fn new(
+        header_rows: usize,
+        footer_rows: usize,
+        log_rows: usize,
+        sidebar_columns: usize,
+    ) -> Layout {
+        let (header, rest) = Rect::full().split_top(header_rows);
+        let (header_separator, rest) = rest.split_top(1);
+        let (rest, footer) = rest.split_bottom(footer_rows);
+        let (mut rest, footer_separator) = rest.split_bottom(1);
+
+        let mut separators = vec![header_separator, footer_separator];
+
+        let mut log = None;
+        if log_rows > 0 {
+            let (top, bottom) = rest.split_bottom(log_rows);
+            let (top, separator) = top.split_bottom(1);
+            rest = top;
+            log = Some(bottom);
+            separators.push(separator);
+        }
+
+        let mut sidebar = None;
+        let mut sidebar_separator = None;
+        if sidebar_columns > 0 {
+            let (left, right) = rest.split_right(sidebar_columns);
+            let (left, separator) = left.split_right(1);
+            rest = left;
+            sidebar = Some(right);
+            sidebar_separator = Some(separator);
+        }
+
+        Layout {
+            header,
+            body: rest,
+            sidebar,
+            log,
+            footer,
+            separators,
+            sidebar_separator,
+        }
+    }
+}
+
+///Grid of cells that panels are drawn into before being shown
+pub struct Canvas {
+    cells: Vec<Vec<char>>,
+}
+
+impl Canvas {
+    pub fn new() -> Canvas {
+        Canvas {
+            cells: vec![vec![' '; COLUMN_WIDTH]; ROW_HEIGHT],
+        }
+    }
+
+    pub fn cells(&self) -> &Vec<Vec<char>> {
+        &self.cells
+    }
+
+    ///Fill an area with one character
+    fn fill(
+        &mut self,
+        rect: Rect,
+        c: char,
+    ) {
+        for row in self.cells.iter_mut().skip(rect.row).take(rect.height) {
+            for cell in row.iter_mut().skip(rect.col).take(rect.width) {
+                *cell = c;
+            }
+        }
+    }
+
+    ///Draw lines of text into an area, anything outside of it is cut off
+    pub fn text(
+        &mut self,
+        rect: Rect,
+        lines: &[String],
+    ) {
+        for (row, line) in self
+            .cells
+            .iter_mut()
+            .skip(rect.row)
+            .take(rect.height)
+            .zip(lines.iter())
+        {
+            for (cell, c) in row
+                .iter_mut()
+                .skip(rect.col)
+                .take(rect.width)
+                .zip(line.chars())
+            {
+                *cell = c;
+            }
+        }
+    }
+
+    ///Draw the lines between panels
+    pub fn separators(
+        &mut self,
+        layout: &Layout,
+    ) {
+        for separator in layout.separators.iter() {
+            self.fill(*separator, '-');
+        }
+        if let Some(separator) = layout.sidebar_separator {
+            self.fill(separator, '|');
+        }
+    }
+}
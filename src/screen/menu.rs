@@ -0,0 +1,191 @@
+use crate::screen::{Screen, ScreenType};
+use crate::utils::*;
+use crossterm::event::KeyCode;
+use rand::Rng;
+
+///Widest the list of a menu gets, the rest of the width is used for the preview
+const MENU_LIST_WIDTH: usize = 28;
+
+///Rows moved by page up and page down in the preview
+const PREVIEW_PAGE: usize = 10;
+
+///Wrap preview text to a width
+///
+///Details are already wrapped for the full screen, so lines carrying on a
+///paragraph at its indent are joined back up before wrapping again
+fn wrap_preview(
+    preview: &str,
+    width: usize,
+) -> Vec<String> {
+    //Paragraphs as the initial indent, subsequent indent and text
+    let mut paragraphs: Vec<(String, String, String)> = Vec::new();
+    for line in preview.lines() {
+        let indent: String = line.chars().take_while(|c| c.is_whitespace()).collect();
+        let text = line.trim_start();
+        if let Some((_, subsequent_indent, paragraph)) = paragraphs.last_mut() {
+            if !text.is_empty()
+                && !text.starts_with("- ")
+                && !paragraph.is_empty()
+                && indent == *subsequent_indent
+            {
+                paragraph.push(' ');
+                paragraph.push_str(text);
+                continue;
+            }
+        }
+        let subsequent_indent = if text.starts_with("- ") {
+            format!("{}  ", indent)
+        } else {
+            indent.clone()
+        };
+        paragraphs.push((indent, subsequent_indent, text.to_string()));
+    }
+
+    let mut lines: Vec<String> = Vec::new();
+    for (indent, subsequent_indent, text) in paragraphs.iter() {
+        let wrapped = textwrap::fill(
+            text,
+            textwrap::Options::new(width)
+                .initial_indent(indent)
+                .subsequent_indent(subsequent_indent),
+        );
+        lines.extend(wrapped.split('\n').map(|s| s.to_string()));
+    }
+    lines
+}
+
+impl Screen {
+    ///Let the player pick an item from a list with the arrow keys
+    ///
+    ///Typing filters the list, and the preview of the highlighted item is shown
+    ///beside it. Returns the index of the picked item, or None if the player
+    ///backs out with escape
+    pub fn draw_menu(
+        &mut self,
+        msg: &str,
+        items: &[String],
+        preview: &dyn Fn(usize) -> String,
+    ) -> Option<usize> {
+        let mut filter = String::new();
+        let mut cursor = 0;
+        let mut top = 0;
+        let mut preview_top = 0;
+
+        loop {
+            let filtered: Vec<usize> = (0..items.len())
+                .filter(|&index| items[index].to_lowercase().contains(&filter.to_lowercase()))
+                .collect();
+            cursor = cursor.min(filtered.len().saturating_sub(1));
+
+            //Keep the cursor inside the visible part of the list
+            let rows = self.msg_rows();
+            let list_rows = rows - 2;
+            if cursor < top {
+                top = cursor;
+            } else if cursor >= top + list_rows {
+                top = cursor + 1 - list_rows;
+            }
+
+            let list_width = items
+                .iter()
+                .map(|item| item.chars().count() + 2)
+                .max()
+                .unwrap_or(0)
+                .min(MENU_LIST_WIDTH);
+            let preview_width = self.msg_columns() - list_width - 3;
+            let preview_lines = match filtered.get(cursor) {
+                Some(&index) => wrap_preview(&preview(index), preview_width),
+                None => Vec::new(),
+            };
+            preview_top = preview_top.min(preview_lines.len().saturating_sub(1));
+
+            let mut list: Vec<String> = Vec::new();
+            for position in top..top + list_rows {
+                list.push(match filtered.get(position) {
+                    Some(&index) => format!(
+                        "{}{}",
+                        if position == cursor { "> " } else { "  " },
+                        items[index]
+                            .chars()
+                            .take(list_width - 2)
+                            .collect::<String>()
+                    ),
+                    None if position == 0 => "  Nothing matches".to_string(),
+                    None => "".to_string(),
+                });
+            }
+            let mut lines: Vec<String> = vec![
+                if filter.is_empty() {
+                    msg.to_string()
+                } else {
+                    format!("Filter: {}", filter)
+                },
+                "".to_string(),
+            ];
+            for (row, line) in list.iter().enumerate() {
+                if preview_lines.is_empty() {
+                    lines.push(line.to_string());
+                    continue;
+                }
+                let preview_line = preview_lines
+                    .get(preview_top + row)
+                    .map(|line| line.as_str())
+                    .unwrap_or("");
+                lines.push(format!(
+                    "{:<width$} | {}",
+                    line.chars().take(list_width).collect::<String>(),
+                    preview_line,
+                    width = list_width
+                ));
+            }
+            self.set_msg(&lines.join("\n"));
+
+            self.screen_type = ScreenType::ChooseMenu;
+            self.set_footer("Arrows: move Type: filter Tab: random PgUp/PgDn: scroll Esc: back");
+            self.draw();
+
+            match enter_key() {
+                KeyCode::Up => {
+                    cursor = cursor.saturating_sub(1);
+                    preview_top = 0;
+                }
+                KeyCode::Down => {
+                    cursor += 1;
+                    preview_top = 0;
+                }
+                KeyCode::Home => {
+                    cursor = 0;
+                    preview_top = 0;
+                }
+                KeyCode::End => {
+                    cursor = filtered.len().saturating_sub(1);
+                    preview_top = 0;
+                }
+                KeyCode::PageUp => preview_top = preview_top.saturating_sub(PREVIEW_PAGE),
+                KeyCode::PageDown => preview_top += PREVIEW_PAGE,
+                KeyCode::Enter if cursor < filtered.len() => return Some(filtered[cursor]),
+                KeyCode::Tab if !filtered.is_empty() => {
+                    let mut rng = rand::thread_rng();
+                    return Some(filtered[rng.gen_range(0..filtered.len())]);
+                }
+                KeyCode::Backspace => {
+                    filter.pop();
+                }
+                //Escape clears the filter first, then backs out
+                KeyCode::Esc => {
+                    if filter.is_empty() {
+                        return None;
+                    }
+                    filter.clear();
+                }
+                KeyCode::Char(c) => {
+                    filter.push(c);
+                    cursor = 0;
+                    top = 0;
+                    preview_top = 0;
+                }
+                _ => {}
+            }
+        }
+    }
+}
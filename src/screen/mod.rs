@@ -1,6 +1,13 @@
+mod layout;
+mod menu;
+
 pub const COLUMN_WIDTH: usize = 80;
 pub const ROW_HEIGHT: usize = 25;
 
+///Columns taken up by the status sidebar when it is shown
+const SIDEBAR_WIDTH: usize = 20;
+
+use crate::screen::layout::{Canvas, Layout};
 use crate::utils::*;
 use crossterm::{
     cursor::{Hide, MoveTo},
@@ -10,9 +17,8 @@ use std::io::{stdout, Write};
 
 enum ScreenType {
     Display,
-    ChooseYesNo,
-    ChooseNumber,
     EnterString,
+    ChooseMenu,
 }
 impl Default for ScreenType {
     fn default() -> Self {
@@ -25,6 +31,8 @@ pub struct Screen {
     header: String,
     footer: String,
     msg: String,
+    log: Vec<String>,
+    sidebar: Vec<String>,
     screen_type: ScreenType,
     buffer: Vec<Vec<char>>,
 }
@@ -65,6 +73,10 @@ impl Screen {
         self.footer = footer.to_string();
     }
 
+    pub fn get_msg(&self) -> String {
+        self.msg.clone()
+    }
+
     pub fn set_msg(
         &mut self,
         msg: &str,
@@ -72,20 +84,74 @@ impl Screen {
         self.msg = msg.to_string();
     }
 
+    pub fn get_log(&self) -> Vec<String> {
+        self.log.clone()
+    }
+
+    ///Set the lines of the log panel shown above the footer, empty hides the panel
+    pub fn set_log(
+        &mut self,
+        log: Vec<String>,
+    ) {
+        self.log = log;
+    }
+
+    pub fn get_sidebar(&self) -> Vec<String> {
+        self.sidebar.clone()
+    }
+
+    ///Set the lines of the sidebar shown right of the message, empty hides the sidebar
+    pub fn set_sidebar(
+        &mut self,
+        sidebar: Vec<String>,
+    ) {
+        self.sidebar = sidebar;
+    }
+
+    ///Rows available for the message, assuming the header and footer are one line
+    pub fn msg_rows(&self) -> usize {
+        self.layout(1, 1).body.height
+    }
+
+    ///Columns available for the message
+    pub fn msg_columns(&self) -> usize {
+        self.layout(1, 1).body.width
+    }
+
     pub fn set_map(
         &mut self,
         map_vec: Vec<Vec<char>>,
         position_x: i32,
         position_y: i32,
+    ) {
+        self.set_map_view(
+            map_vec,
+            position_x,
+            position_y,
+            &[(
+                Point {
+                    col: position_x as usize,
+                    row: position_y as usize,
+                },
+                '@',
+            )],
+        );
+    }
+
+    ///Set the message to a window of the map centered on a position
+    ///
+    ///Markers are drawn over the map symbols
+    pub fn set_map_view(
+        &mut self,
+        map_vec: Vec<Vec<char>>,
+        position_x: i32,
+        position_y: i32,
+        markers: &[(Point, char)],
     ) {
         //Generate top coordinate of map, may be negative
         //Assume header/footer are one line, cut off later if not
-        let msg_area_width = COLUMN_WIDTH;
-        let msg_area_height = ROW_HEIGHT as u32
-                          - 1 //Header
-                          - 1 //Spacer
-                          - 1 //Bottom Spacer
-                          - 1; //Footer
+        let msg_area_width = self.msg_columns();
+        let msg_area_height = self.msg_rows();
 
         let top_pos_x: i32 = position_x as i32 - msg_area_width as i32 / 2;
         let top_pos_y: i32 = position_y as i32 - msg_area_height as i32 / 2;
@@ -100,11 +166,17 @@ impl Screen {
                 {
                     msg_string = format!("{} ", msg_string);
                 } else {
-                    //Draw Pat player position
-                    if x == position_x && y == position_y {
-                        msg_string = format!("{}{}", msg_string, '@');
-                    } else {
-                        msg_string = format!("{}{}", msg_string, map_vec[y as usize][x as usize]);
+                    //Draw markers such as the player position
+                    let p = Point {
+                        col: x as usize,
+                        row: y as usize,
+                    };
+                    match markers.iter().find(|(marker_p, _)| *marker_p == p) {
+                        Some((_, marker)) => msg_string = format!("{}{}", msg_string, marker),
+                        None => {
+                            msg_string =
+                                format!("{}{}", msg_string, map_vec[y as usize][x as usize])
+                        }
                     }
                 }
             }
@@ -114,33 +186,18 @@ impl Screen {
         self.msg = msg_string;
     }
 
-    pub fn draw_display(&mut self) {
+    ///Draw the screen without waiting for input
+    pub fn draw_update(&mut self) {
         self.screen_type = ScreenType::Display;
-        self.set_footer("Press Enter to continue...");
-        self.draw();
-        pause();
-    }
-
-    pub fn draw_pick_yes_or_no(
-        &mut self,
-        msg: &str,
-    ) -> bool {
-        self.screen_type = ScreenType::ChooseYesNo;
-        self.set_footer(&format!("{} (Y/n)", &msg));
+        self.set_footer("");
         self.draw();
-        pick_yes_or_no()
     }
 
-    pub fn draw_pick_a_number(
-        &mut self,
-        msg: &str,
-        low: u32,
-        high: u32,
-    ) -> u32 {
-        self.screen_type = ScreenType::ChooseNumber;
-        self.set_footer(&format!("{} {}-{} ", &msg, low, high));
+    pub fn draw_display(&mut self) {
+        self.screen_type = ScreenType::Display;
+        self.set_footer("Press Enter to continue...");
         self.draw();
-        pick_number(low, high)
+        pause();
     }
 
     pub fn draw_enter_string(
@@ -163,191 +220,75 @@ impl Screen {
         enter_char()
     }
 
-    fn draw(&mut self) {
-        //New buffer
-        let mut buffer_new: Vec<Vec<char>>;
-        let mut buffer_column;
-
-        //Position of message box
-        let mut position = 0;
-
-        //Process Header
-        let header_formatted = textwrap::fill(
-            &self.header,
-            textwrap::Options::new(COLUMN_WIDTH)
-                .initial_indent("")
-                .subsequent_indent(""),
-        );
-        let header_formatted_vec: Vec<String> = header_formatted
-            .split('\n')
-            .map(|s| s.to_string())
-            .collect();
-        let header_line_count = count_newlines(&header_formatted);
-
-        //Process Message
-        let msg_formatted = strip_trailing_newline(&textwrap::fill(
-            &self.msg,
-            textwrap::Options::new(COLUMN_WIDTH)
-                .initial_indent("")
-                .subsequent_indent(""),
-        ))
-        .to_string();
-        let msg_formatted_vec: Vec<String> =
-            msg_formatted.split('\n').map(|s| s.to_string()).collect();
-        let msg_line_count = count_newlines(&msg_formatted);
-
-        //Process Footer
-        let footer_formatted = textwrap::fill(
-            &self.footer,
-            textwrap::Options::new(COLUMN_WIDTH)
+    ///Wrap text to a width, one string per line
+    fn wrap(
+        text: &str,
+        width: usize,
+    ) -> Vec<String> {
+        textwrap::fill(
+            text,
+            textwrap::Options::new(width)
                 .initial_indent("")
                 .subsequent_indent(""),
-        );
-        let footer_formatted_vec: Vec<String> = footer_formatted
-            .split('\n')
-            .map(|s| s.to_string())
-            .collect();
-        let footer_line_count = count_newlines(&footer_formatted);
-
-        //Determine area available for message
-        let msg_area = ROW_HEIGHT as u32
-                          - header_line_count //Header
-                          - 1 //Spacer
-                          - 1 //Bottom Spacer
-                          - footer_line_count; //Footer
-
-        //While the message is too big for the current area, need to do scrolling
-        while msg_line_count - position > msg_area {
-            //Initialize new buffer
-            buffer_new = vec![vec![' '; COLUMN_WIDTH]; ROW_HEIGHT];
-            buffer_column = 0;
-
-            //Process temp footer
-            let footer_formatted = textwrap::fill(
-                "Press enter to continue",
-                textwrap::Options::new(COLUMN_WIDTH)
-                    .initial_indent("")
-                    .subsequent_indent(""),
-            );
-            let footer_formatted_vec: Vec<String> = footer_formatted
-                .split('\n')
-                .map(|s| s.to_string())
-                .collect();
-            let footer_line_count = count_newlines(&footer_formatted);
-
-            //Determine temp area available for message
-            let msg_area = ROW_HEIGHT as u32
-                          - header_line_count //Header
-                          - 1 //Spacer
-                          - 1 //Ellipses
-                          - 1 //Bottom Spacer
-                          - footer_line_count; //Footer
-
-            //Insert header
-            for header_formatted in header_formatted_vec.iter() {
-                buffer_new[buffer_column]
-                    .splice(0..header_formatted.len(), header_formatted.chars());
-                buffer_column += 1;
-            }
-
-            //Insert seperator
-            buffer_new[buffer_column] = vec!['-'; COLUMN_WIDTH];
-            buffer_column += 1;
-
-            //Insert message
-            for n in 0..msg_area {
-                buffer_new[buffer_column].splice(
-                    0..msg_formatted_vec[(n + position) as usize].len(),
-                    msg_formatted_vec[(n + position) as usize].chars(),
-                );
-                buffer_column += 1;
-            }
-            position += (msg_area * 3) / 4;
-
-            //Insert ellipsis
-            buffer_new[buffer_column].splice(0..3, vec!['.'; 3]);
-            buffer_column += 1;
-
-            //Insert seperator
-            buffer_new[buffer_column] = vec!['-'; COLUMN_WIDTH];
-            buffer_column += 1;
-
-            //Insert footer
-            for footer_formatted in footer_formatted_vec.iter() {
-                buffer_new[buffer_column]
-                    .splice(0..footer_formatted.len(), footer_formatted.chars());
-                buffer_column += 1;
-            }
-
-            //Update buffer/stdout with modified cells
-            #[allow(clippy::clippy::needless_range_loop)]
-            for row in 0..ROW_HEIGHT {
-                for col in 0..COLUMN_WIDTH {
-                    if self.buffer[row][col] != buffer_new[row][col] {
-                        self.buffer[row][col] = buffer_new[row][col];
-                        stdout().execute(MoveTo(col as u16, row as u16)).unwrap();
-                        stdout().write_all(&[self.buffer[row][col] as u8]).unwrap();
-                    }
-                }
-            }
-
-            //Move curser to footer
-            stdout()
-                .execute(MoveTo(0, (ROW_HEIGHT - 1) as u16))
-                .unwrap();
-
-            //Flush changes
-            stdout().flush().unwrap();
-
-            //Wait for enter
-            pause();
-        }
+        )
+        .split('\n')
+        .map(|s| s.to_string())
+        .collect()
+    }
 
-        //At this point the message can fit into the current area, just print and add spacing
-        buffer_new = vec![vec![' '; COLUMN_WIDTH]; ROW_HEIGHT];
-        buffer_column = 0;
+    ///Layout for the current panels with the given header and footer
+    fn layout(
+        &self,
+        header_rows: usize,
+        footer_rows: usize,
+    ) -> Layout {
+        Layout::new(
+            header_rows,
+            footer_rows,
+            self.log.len(),
+            if self.sidebar.is_empty() {
+                0
+            } else {
+                SIDEBAR_WIDTH
+            },
+        )
+    }
 
-        //Insert header
-        for header_formatted in header_formatted_vec.iter() {
-            buffer_new[buffer_column].splice(0..header_formatted.len(), header_formatted.chars());
-            buffer_column += 1;
+    ///Draw every panel into a canvas
+    fn compose(
+        &self,
+        layout: &Layout,
+        header: &[String],
+        msg: &[String],
+        footer: &[String],
+    ) -> Canvas {
+        let mut canvas = Canvas::new();
+        canvas.text(layout.header, header);
+        canvas.text(layout.body, msg);
+        if let Some(sidebar) = layout.sidebar {
+            canvas.text(sidebar, &self.sidebar);
         }
-
-        //Insert seperator
-        buffer_new[buffer_column] = vec!['-'; COLUMN_WIDTH];
-        buffer_column += 1;
-
-        //Insert message
-        for n in 0..(msg_formatted_vec.len() - position as usize) {
-            buffer_new[buffer_column].splice(
-                0..msg_formatted_vec[n + position as usize].len(),
-                msg_formatted_vec[n + position as usize].chars(),
-            );
-            buffer_column += 1;
+        if let Some(log) = layout.log {
+            canvas.text(log, &self.log);
         }
+        canvas.text(layout.footer, footer);
+        canvas.separators(layout);
+        canvas
+    }
 
-        //Move to end of message
-        buffer_column = (ROW_HEIGHT as u32
-                        - 1 //Bottom Spacer
-                        - footer_line_count //Footer
-                        - 1) as usize;
-
-        //Insert seperator
-        buffer_new[buffer_column] = vec!['-'; COLUMN_WIDTH];
-        buffer_column += 1;
-
-        //Insert footer
-        for footer_formatted in footer_formatted_vec.iter() {
-            buffer_new[buffer_column].splice(0..footer_formatted.len(), footer_formatted.chars());
-            buffer_column += 1;
-        }
+    ///Write the cells that changed since the last draw to stdout
+    fn flush(
+        &mut self,
+        canvas: &Canvas,
+    ) {
+        let cells = canvas.cells();
 
         //Update buffer/stdout with modified cells
         #[allow(clippy::clippy::needless_range_loop)]
         for row in 0..ROW_HEIGHT {
             for col in 0..COLUMN_WIDTH {
-                if self.buffer[row][col] != buffer_new[row][col] {
-                    self.buffer[row][col] = buffer_new[row][col];
+                if self.buffer[row][col] != cells[row][col] {
+                    self.buffer[row][col] = cells[row][col];
                     stdout().execute(MoveTo(col as u16, row as u16)).unwrap();
                     stdout().write_all(&[self.buffer[row][col] as u8]).unwrap();
                 }
@@ -363,4 +304,32 @@ impl Screen {
         //Flush changes
         stdout().flush().unwrap();
     }
+
+    fn draw(&mut self) {
+        let header = Screen::wrap(&self.header, COLUMN_WIDTH);
+        let footer = Screen::wrap(&self.footer, COLUMN_WIDTH);
+        let layout = self.layout(header.len(), footer.len());
+        let msg: Vec<String> = Screen::wrap(strip_trailing_newline(&self.msg), layout.body.width);
+
+        //While the message is too big for the body, show it a page at a time
+        let mut position = 0;
+        while msg.len() - position > layout.body.height {
+            let page_footer = Screen::wrap("Press enter to continue", COLUMN_WIDTH);
+            let page_layout = self.layout(header.len(), page_footer.len());
+            let page_rows = page_layout.body.height - 1; //Ellipsis
+
+            let mut page: Vec<String> = msg[position..position + page_rows].to_vec();
+            page.push("...".to_string());
+            let canvas = self.compose(&page_layout, &header, &page, &page_footer);
+            self.flush(&canvas);
+            position += (page_rows * 3) / 4;
+
+            //Wait for enter
+            pause();
+        }
+
+        //At this point the message fits
+        let canvas = self.compose(&layout, &header, &msg[position..], &footer);
+        self.flush(&canvas);
+    }
 }
@@ -1,10 +1,9 @@
+use crossterm::event::{self, Event, KeyCode};
 use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use rand::Rng;
-use regex::Regex;
 use serde::{Deserialize, Serialize};
 use std::fmt;
 use std::io::{self, stdin, stdout, Read, Write};
-use unicode_segmentation::UnicodeSegmentation;
 
 ///Struct indicating a point on the game grid
 #[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
@@ -24,6 +23,95 @@ pub fn d(num: u32) -> u32 {
     rng.gen_range(1..(num + 1))
 }
 
+///Roll a d20, advantage rolls twice keeping the higher and disadvantage the
+///lower, having both cancels out
+pub fn d20(
+    advantage: bool,
+    disadvantage: bool,
+) -> u32 {
+    let roll = d(20);
+    match (advantage, disadvantage) {
+        (true, false) => roll.max(d(20)),
+        (false, true) => roll.min(d(20)),
+        _ => roll,
+    }
+}
+
+///Dice expression such as 2d6+1, a count of dice plus a flat bonus
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Dice {
+    pub count: u32,
+    pub sides: u32,
+    pub bonus: i32,
+}
+
+impl Dice {
+    ///Parse dice written as 1d8, 2d6+1, 1d4-1 or a flat number such as 1
+    pub fn parse(dice: &str) -> Option<Dice> {
+        let dice = dice.trim();
+        let (dice, bonus) = match dice.find(['+', '-']) {
+            Some(index) => (&dice[..index], dice[index..].parse::<i32>().ok()?),
+            None => (dice, 0),
+        };
+        match dice.split_once('d') {
+            Some((count, sides)) => Some(Dice {
+                count: if count.is_empty() {
+                    1
+                } else {
+                    count.parse().ok()?
+                },
+                sides: sides.parse().ok()?,
+                bonus,
+            }),
+            None if dice.is_empty() => Some(Dice {
+                bonus,
+                ..Default::default()
+            }),
+            None => Some(Dice {
+                bonus: bonus + dice.parse::<i32>().ok()?,
+                ..Default::default()
+            }),
+        }
+    }
+
+    ///Roll the dice, never less than zero
+    pub fn roll(&self) -> u32 {
+        let total: i32 = (0..self.count).map(|_| d(self.sides) as i32).sum::<i32>() + self.bonus;
+        total.max(0) as u32
+    }
+
+    ///Roll only the dice, as the extra dice of a critical hit
+    pub fn roll_dice(&self) -> u32 {
+        (0..self.count).map(|_| d(self.sides)).sum()
+    }
+}
+
+impl fmt::Display for Dice {
+    fn fmt(
+        &self,
+        f: &mut fmt::Formatter,
+    ) -> fmt::Result {
+        if self.count == 0 {
+            return write!(f, "{}", self.bonus);
+        }
+        write!(f, "{}d{}", self.count, self.sides)?;
+        if self.bonus != 0 {
+            write!(f, "{:+}", self.bonus)?;
+        }
+        Ok(())
+    }
+}
+
+///Number of moves between two points, diagonals counting as one
+pub fn distance(
+    p0: &Point,
+    p1: &Point,
+) -> usize {
+    (p0.col as i32 - p1.col as i32)
+        .abs()
+        .max((p0.row as i32 - p1.row as i32).abs()) as usize
+}
+
 ///Clears the console
 pub fn clear() {
     print!("{esc}c", esc = 27 as char);
@@ -37,23 +125,6 @@ pub fn pause() {
     stdin().read_line(&mut tmp).unwrap();
 }
 
-///Prompts user to pick yes or not
-///
-///If an empty response is given, assumes yes
-pub fn pick_yes_or_no() -> bool {
-    let mut stdout = stdout();
-    stdout.flush().unwrap();
-    let mut my_yes_or_no_str = String::new();
-    stdin().read_line(&mut my_yes_or_no_str).unwrap();
-
-    //regex for empty/y*/Y*
-    let re_yes = Regex::new(r"^(?i)\s*y(es)?\s*$").unwrap();
-    if re_yes.is_match(&my_yes_or_no_str) || my_yes_or_no_str.trim().is_empty() {
-        return true;
-    }
-    false
-}
-
 ///Prompts user to enter a string
 pub fn enter_string() -> String {
     let mut stdout = stdout();
@@ -84,41 +155,18 @@ pub fn enter_char() -> char {
     my_char
 }
 
-///Prompts user to pick a number in a given range
-///
-///If an empty string is given, a random number is chosen
-///If a non-number is given, the user is reprompted
-pub fn pick_number(
-    low: u32,
-    high: u32,
-) -> u32 {
+///Waits for a key press, including keys like arrows that send several bytes
+pub fn enter_key() -> KeyCode {
     let mut stdout = stdout();
-    loop {
-        stdout.flush().unwrap();
-        let mut my_number_str = String::new();
-        stdin().read_line(&mut my_number_str).unwrap();
-
-        if my_number_str.trim().is_empty() {
-            let mut rng = rand::thread_rng();
-            return rng.gen_range(low..(high + 1));
-        } else if my_number_str.trim().parse::<u32>().is_ok() {
-            let my_number: u32 = my_number_str.trim().parse().unwrap();
-            if my_number >= low && my_number <= high {
-                return my_number;
-            }
-        }
-    }
-}
-
-///Counts the number of newlines in a string
-pub fn count_newlines(msg: &str) -> u32 {
-    let mut count = 1;
-    for c in msg.graphemes(true) {
-        if c == "\n" {
-            count += 1;
+    stdout.flush().unwrap();
+    enable_raw_mode().unwrap();
+    let key = loop {
+        if let Event::Key(key_event) = event::read().unwrap() {
+            break key_event.code;
         }
-    }
-    count
+    };
+    disable_raw_mode().unwrap();
+    key
 }
 
 /// Generates a vector of points from (x0,y0) to (x1,y1)
@@ -145,7 +193,7 @@ fn vec_between_points_low(
     p0: &Point,
     p1: &Point,
 ) -> Vec<Point> {
-    let dx:i32 = p1.col as i32 - p0.col as i32;
+    let dx: i32 = p1.col as i32 - p0.col as i32;
     let mut dy: i32 = p1.row as i32 - p0.row as i32;
     let mut yi: i32 = 1;
     if dy < 0 {
@@ -157,7 +205,10 @@ fn vec_between_points_low(
 
     let mut line: Vec<Point> = Vec::new();
     for x in p0.col..=p1.col {
-        line.push(Point { col: x, row: y as usize});
+        line.push(Point {
+            col: x,
+            row: y as usize,
+        });
         if diff > 0 {
             y += yi;
             diff += 2 * (dy - dx);
@@ -179,12 +230,15 @@ fn vec_between_points_high(
         xi = -1;
         dx = -dx;
     }
-    let mut diff:i32 = (2 * dx) - dy;
-    let mut x:i32 = p0.col as i32;
+    let mut diff: i32 = (2 * dx) - dy;
+    let mut x: i32 = p0.col as i32;
 
     let mut line: Vec<Point> = Vec::new();
     for y in p0.row..=p1.row {
-        line.push(Point { col:x as usize, row: y });
+        line.push(Point {
+            col: x as usize,
+            row: y,
+        });
         if diff > 0 {
             x += xi;
             diff += 2 * (dx - dy);